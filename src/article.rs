@@ -1,32 +1,76 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
 pub mod article {
-	use macroquad::{math::vec2, math::Rect, math::Vec2, texture::{Texture2D, DrawTextureParams, draw_texture_ex}, color::{WHITE, RED}, shapes::draw_rectangle_lines, input::is_key_down, miniquad::KeyCode};
-	use std::{fmt::{ Display, Formatter, Result as FmtResult }, collections::HashMap, time::SystemTime};
-	use chrono::prelude::*;
+	use macroquad::{math::vec2, math::Rect, math::Vec2, texture::{Texture2D, DrawTextureParams, draw_texture_ex}, color::{WHITE, RED}, shapes::{draw_line, draw_rectangle_lines}};
+	use std::{fmt::{ Display, Formatter, Result as FmtResult }, collections::HashMap, rc::Rc};
+	use crate::input::input::InputState;
+	use crate::broadphase::broadphase::SpatialGrid;
+	use crate::component::component::Components;
+	use crate::material::material::{Contact, Material};
+	use crate::shape::shape::{Penetration, Polygon};
+	use crate::sheet::sheet::SpriteSheet;
+	use crate::world::world::{DT, GRAVITY};
+	use serde::Deserialize;
 
-	pub trait Element {
-		fn tick(&self);
-		fn draw(&self);
-	}
-	
+	/**
+	 * Speed in pixels per second under which an article is considered to be at rest
+	 */
+	const REST_SPEED: f32 = 3.0;
+	const WALKABLE_NORMAL: f32 = 0.5;	//Surfaces facing further up than this count as ground, around 60 degrees of slope
+	const STICK_SPEED: f32 = GRAVITY * DT;	//Speed along a slope under which a grippy article comes to rest
+	const SNAP_DISTANCE: f32 = 8.0;	//Pixels a slope can fall away under an article in one step and still be followed
+	const SLIPPERY_FRICTION: f32 = 0.046;	//Contacts keeping at least this much speed per second slide down slopes instead of sticking
+	const ONE_WAY_TOLERANCE: f32 = 8.0;	//Pixels an article can have sunk into a one way bound and still land on it
+	const BOUNCE_SPEED: f32 = 60.0;	//Pixels per second, smaller bounces are dropped so resting articles settle
+	const OVERLAP_TOLERANCE: f32 = 2.0;	//Pixels of overlap ignored, so resting on or brushing past something doesn't count
+
+	pub type TickFn = fn(&mut Article, &mut HashMap<String, Article>, &SpatialGrid, &InputState);
+	pub type CollideFn = fn(axis: Vec2, top: &mut Article, bottom: &mut Article, intersection: &Rect) -> CollisionResult;
+	pub type DrawFn = fn(&mut Article) -> bool;
+	pub type PostPhysicsFn = fn(&mut Article, &mut HashMap<String, Article>);
+
+	#[derive(Clone)]
 	pub struct Article {
 		pub name: String,	//Name should be unique to the scene
 		pub texture: Option<Texture2D>,
+		pub sheet: Option<Rc<SpriteSheet>>,	//Anchor points for the frames of the texture
 		pub pos: Vec2,
+		pub prev_pos: Vec2,	//Position at the start of the current step, drawing interpolates from here to pos
 		pub params: DrawTextureParams,
 		pub bounds: Option<Vec<Rect>>,
-		pub vel: Vec2,	//Velocity
+		pub materials: Vec<Material>,	//Material of each bound in order, bounds past the end have none
+		pub footing: Option<Material>,	//Material of the bound last landed on or stood on
+		pub one_way: Vec<usize>,	//Indices into bounds that only block articles coming down onto their top
+		pub dropping_through: Option<String>,	//One way article being fallen through, ignored until clear of it
+		pub in_water: Option<String>,	//Water the article overlaps, set by the world step
+		pub submerged: f32,	//Fraction of the article's bounds under water
+		pub polygons: Option<Vec<Polygon>>,	//Convex shapes other articles collide against, like slopes
+		pub polygon_materials: Vec<Material>,	//Material of each polygon in order, like materials for bounds
+		pub vel: Vec2,	//Velocity in pixels per second
 		pub mass: f32,
 		pub cog: Vec2,	//Center of Gravity
-		pub friction_coefficient: f32,	//How slow does this slow down on ground
+		pub friction_coefficient: f32,	//Fraction of its speed kept after a second on the ground
 		pub elasticity: f32,	//Used to determine how collisions react with different materials
 		pub do_destroy: bool,	//Track whether to remove an article at the end of it's next game loop
-		pub tick: Option<fn(&mut Article, &mut HashMap<String, Article>)>,
-		pub do_collide: Option<fn(axis: Vec2
-			, top: &mut Article, bottom: &mut Article, intersection: &Rect) -> CollisionResult>,
-		pub draw: Option<fn(&mut Article) -> bool>,	//Option to draw extras in the context of the article
+		pub tick: Option<TickFn>,
+		pub do_collide: Option<CollideFn>,
+		pub post_physics: Option<PostPhysicsFn>,	//Runs once every article has moved this step
+		pub draw: Option<DrawFn>,	//Option to draw extras in the context of the article
 		pub attached: Option<String>,	//Name of attached article, used to map items together
 		pub attached_to: Vec<String>,
-		pub scratchpad: HashMap<String, f32>
+		pub components: Components,
+		pub reset_on_respawn: bool,	//Put back the way the level built it whenever the player respawns
+		pub trigger: bool,	//Reacts to overlaps without blocking anything, like exits and checkpoints
+		pub out_of_bounds: OutOfBounds,	//What happens once the article leaves the level's bounds
+		pub layer: RenderLayer,
+		pub z: i32,	//Draw order within the layer, higher is nearer the front
+		pub events: Vec<Event>	//Raised by behaviours, drained by the game loop each frame
+	}
+
+	/**
+	 * Whether a one way bound blocks an article, only when the article is on top of it
+	 */
+	fn one_way_blocks(axis: Vec2, upper: Rect, lower: Rect) -> bool {
+		axis.y == 1.0 && upper.bottom() <= lower.y + ONE_WAY_TOLERANCE
 	}
 
 	impl Article {
@@ -34,8 +78,18 @@ pub mod article {
 			Self {
 				name: String::from("Article"),
 				texture: None,
+				sheet: None,
 				pos: vec2(dest.x.to_owned(), dest.y.to_owned()),
+				prev_pos: vec2(dest.x.to_owned(), dest.y.to_owned()),
 				bounds: bounds.to_owned(),
+				materials: Vec::new(),
+				footing: None,
+				one_way: Vec::new(),
+				dropping_through: None,
+				in_water: None,
+				submerged: 0.0,
+				polygons: None,
+				polygon_materials: Vec::new(),
 				params: DrawTextureParams {
 					dest_size: Some(vec2(dest.w, dest.h)),
 					source: Some(src),
@@ -48,72 +102,174 @@ pub mod article {
 				mass: 1.0,
 				vel: vec2(0.0, 0.0),
 				cog: vec2(dest.w / 2.0, dest.h / 2.0),
-				friction_coefficient: 0.9,
+				friction_coefficient: 0.0018,
 				elasticity: 0.01,
 				tick: None,
 				do_collide: None,
+				post_physics: None,
 				draw: None,
 				attached: None,
 				attached_to: Vec::new(),
-				scratchpad: HashMap::new()
+				components: Components::default(),
+				reset_on_respawn: false,
+				trigger: false,
+				out_of_bounds: OutOfBounds::Destroy,
+				layer: RenderLayer::Main,
+				z: 0,
+				events: Vec::new()
 			}
 		}
 
-		pub async fn load_texture(&mut self, texture_path: &str) -> Option<Texture2D> {
-			match macroquad::texture::load_texture(texture_path).await {
-				Ok(t) => self.texture = Some(t),
-				Err(e) => println!("Could not load texture: {}", e)
-			};
-			self.texture.clone()
-		}
-
 		/**
-		 * Position is normalized against the dest_size
+		 * Position is the column and row of a source sized cell on the spritesheet
 		 */
 		pub fn set_frame(&mut self, position: Vec2) {
 			if let Some(source) = self.params.source {
-				if let Some(dest) = self.params.dest_size {
-					let delta = dest * position;
-					let mut new_source: Rect = Rect::new(delta.x, delta.y, source.w, source.h);
-					if let Some(texture) = &self.texture {
-						if (new_source.x + new_source.w) >= texture.size().x {
-							new_source.x = 0.0;
-						}
-						if (new_source.y + new_source.h) >= texture.size().y {
-							new_source.y = 0.0;
-						}
+				let delta = source.size() * position;
+				let mut new_source: Rect = Rect::new(delta.x, delta.y, source.w, source.h);
+				if let Some(texture) = &self.texture {
+					if (new_source.x + new_source.w) > texture.size().x {
+						new_source.x = 0.0;
+					}
+					if (new_source.y + new_source.h) > texture.size().y {
+						new_source.y = 0.0;
 					}
-					self.params.source = Some(new_source);
 				}
+				self.params.source = Some(new_source);
 			}
 		}
-		pub fn increment_frame(&mut self, axis: Vec2) {
-			if let Some(source) = self.params.source {
-				if let Some(dest) = self.params.dest_size {
-					let delta = dest * axis;
-					let mut new_source = Rect::new(source.x + delta.x, source.y + delta.y, source.w, source.h);
-					if let Some(texture) = &self.texture {
-						if (new_source.x + new_source.w) > texture.size().x {
-							new_source.x = 0.0;
-						}
-						if (new_source.y + new_source.h) > texture.size().y {
-							new_source.y = 0.0;
-						}
-					}
-					self.params.source = Some(new_source);
+
+		/**
+		 * World position of a named anchor on the frame currently shown, following the sprite's scale and flip
+		 */
+		pub fn anchor(&self, name: &str) -> Option<Vec2> {
+			let source = self.params.source?;
+			let column = (source.x / source.w).round() as u32;
+			let row = (source.y / source.h).round() as u32;
+			let mut offset = self.sheet.as_ref()?.anchor(column, row, name)?;
+			let dest = self.params.dest_size.unwrap_or(source.size());
+			offset *= dest / source.size();
+			if self.params.flip_x {
+				offset.x = dest.x - offset.x;
+			}
+			if self.params.flip_y {
+				offset.y = dest.y - offset.y;
+			}
+			Some(self.pos + offset)
+		}
+
+		/**
+		 * Switch to a named animation clip, does nothing if the article isn't animated
+		 */
+		pub fn play(&mut self, clip: &str) {
+			if let Some(animation) = &mut self.components.animation {
+				animation.play(clip);
+			}
+		}
+
+		pub fn animation_finished(&self, clip: &str) -> bool {
+			self.components.animation.as_ref().is_some_and(|animation| animation.just_finished(clip))
+		}
+
+		pub fn material(&self, bound_index: usize) -> Option<Material> {
+			self.materials.get(bound_index).copied()
+		}
+
+		pub fn polygon_material(&self, polygon_index: usize) -> Option<Material> {
+			self.polygon_materials.get(polygon_index).copied()
+		}
+
+		pub fn is_pose(&self, name: &str) -> bool {
+			self.components.poses.as_ref().is_some_and(|poses| poses.current == name)
+		}
+
+		/**
+		 * Switch to a named bounds set, keeping the bottom of the bounds where it was.
+		 * Refused if the new bounds would overlap something solid, so a crouched article can't stand up under a ceiling
+		 */
+		pub fn set_pose(&mut self, name: &str, articles: &HashMap<String, Article>, grid: &SpatialGrid) -> bool {
+			let pose = match &self.components.poses {
+				Some(poses) if poses.current == name => return true,
+				Some(poses) => match poses.sets.get(name) {
+					Some(pose) => pose.clone(),
+					None => return false
+				},
+				None => return false
+			};
+			let dest = self.params.dest_size.unwrap_or_default();
+			let bounds: Vec<Rect> = pose.bounds.iter().map(|b| {
+				let mut bound = Rect::new(b.0, b.1, b.2, b.3);
+				if self.params.flip_x {
+					bound.x = dest.x - bound.x - bound.w;
 				}
+				if self.params.flip_y {
+					bound.y = dest.y - bound.y - bound.h;
+				}
+				bound
+			}).collect();
+			let bottom = |bounds: &[Rect]| bounds.iter().map(|bound| bound.bottom()).reduce(f32::max);
+			let shift = match (self.bounds.as_deref().and_then(bottom), bottom(&bounds)) {
+				(Some(old), Some(new)) => vec2(0.0, old - new),
+				_ => Vec2::ZERO
+			};
+
+			let pos = self.pos + shift;
+			let Some(area) = bounds.iter().map(|bound| bound.offset(pos)).reduce(|a, b| a.combine_with(b)) else {
+				return false;
+			};
+			let blocked = grid.query(area).into_iter()
+				.filter_map(|other| articles.get(other))
+				.filter(|other| other.mass.is_infinite() && !other.trigger)
+				.any(|other| {
+					other.bounds.iter().flatten().any(|other_bound| {
+						bounds.iter().any(|bound| match bound.offset(pos).intersect(other_bound.offset(other.pos)) {
+							Some(overlap) => overlap.w > OVERLAP_TOLERANCE && overlap.h > OVERLAP_TOLERANCE,
+							None => false
+						})
+					})
+				});
+			if blocked {
+				return false;
 			}
+
+			self.pos = pos;
+			self.prev_pos += shift;
+			self.bounds = Some(bounds);
+			self.params.rotation = pose.rotation;
+			if let Some(poses) = &mut self.components.poses {
+				poses.current = name.to_string();
+			}
+			true
 		}
 
-		pub fn draw(&mut self) {
+		/**
+		 * Where to draw the article, blended between its last two simulated positions
+		 */
+		pub fn render_pos(&self, alpha: f32) -> Vec2 {
+			self.prev_pos.lerp(self.pos, alpha)
+		}
+
+		/**
+		 * Alpha is how far the renderer is between the previous step and the current one
+		 */
+		pub fn draw(&mut self, alpha: f32) {
+			//Draw functions see the interpolated position, the simulated one is put back afterwards
+			let simulated_pos = self.pos;
+			self.pos = self.render_pos(alpha);
+			if let Some(v) = self.params.dest_size {
+				self.params.pivot = Some(self.pos + (v / vec2(2.0, 2.0)));
+			}
+
 			let mut draw_texture = true;
 			if let Some(draw_func) = self.draw {
 				draw_texture = (draw_func)(self);
 			}
+			if let Some(health) = &self.components.health {
+				draw_texture &= !health.blinked_out();
+			}
 			if draw_texture {
-				match &self.texture {
-					Some(t) => draw_texture_ex(&t, self.pos.x, self.pos.y, WHITE, self.params.clone()),
-					None => ()
+				if let Some(t) = &self.texture {
+					draw_texture_ex(t, self.pos.x, self.pos.y, WHITE, self.params.clone());
 				}
 				if let Some(bounds) = &self.bounds {
 					for bound in bounds.iter() {
@@ -121,7 +277,14 @@ pub mod article {
 						draw_rectangle_lines(bound_delta.x, bound_delta.y, bound_delta.w, bound_delta.h, 5.0, RED);
 					}
 				}
+				for polygon in self.polygons.iter().flatten() {
+					let points = polygon.offset(self.pos).points;
+					for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+						draw_line(a.x, a.y, b.x, b.y, 5.0, RED);
+					}
+				}
 			}
+			self.pos = simulated_pos;
 		}
 
 		pub fn set_direction(&mut self, axis: Vec2) {
@@ -144,6 +307,17 @@ pub mod article {
 				}
 			}
 
+			if let (Some(polygons), Some(dest)) = (&mut self.polygons, self.params.dest_size) {
+				for polygon in polygons.iter_mut() {
+					if self.params.flip_x != (axis == Vec2::X) && axis.x != 0.0 {
+						polygon.mirror_x(dest.x);
+					}
+					if self.params.flip_y != (axis == Vec2::Y) && axis.y != 0.0 {
+						polygon.mirror_y(dest.y);
+					}
+				}
+			}
+
 			if axis == Vec2::X {
 				self.params.flip_x = true;
 			} else if axis == -Vec2::X {
@@ -157,53 +331,42 @@ pub mod article {
 		}
 
 		pub fn update_health(&mut self, delta_health: f32) {
-			let seconds_since_midnight = Utc::now().num_seconds_from_midnight() as f32;
-			if let Some(most_recent_damage_time) = self.scratchpad.get("most_recent_damage") {
-				if seconds_since_midnight - *most_recent_damage_time <= 2.0 {
+			if let Some(health) = &mut self.components.health {
+				if delta_health < 0.0 && health.is_invulnerable() {
 					return;	//Article was damaged too recently. Do not continue
 				}
-			}
 
-			let avail_health = match self.scratchpad.get("avail_health") {
-				Some(h) => *h,
-				None => 5.0
-			};
-
-			if let Some(health) = self.scratchpad.get_mut("health") {
-				if (*health + delta_health) > avail_health {
-					*health = avail_health;
+				if (health.current + delta_health) > health.max {
+					health.current = health.max;
 				} else {
-					*health += delta_health;
+					health.current += delta_health;
+				}
+				if delta_health < 0.0 {
+					//If damaged, don't allow damage again for certain number of seconds
+					health.invulnerable_for = health.invulnerable_seconds;
 				}
-			}
-			if delta_health < 0.0 {
-				//If damaged, don't allow damage again for certain number of seconds
-				self.scratchpad.insert("most_recent_damage".to_string(), seconds_since_midnight);
 			}
 		}
 
 
-		pub fn tick(&mut self, articles: &mut HashMap<String, Article>) {
+		pub fn tick(&mut self, articles: &mut HashMap<String, Article>, grid: &SpatialGrid, input: &InputState) {
 			if let Some(v) = self.params.dest_size {
 				self.params.pivot = Some(self.pos + (v / vec2(2.0, 2.0)));
 			}
-			self.attached_to.iter().for_each(|key| {
-				if let Some(attachment) = articles.get_mut(key) {
-				}
-			});
+			if let Some(health) = &mut self.components.health {
+				health.cool_down(DT);
+			}
+			if let Some(animation) = &mut self.components.animation {
+				animation.advance(DT);
+			}
 			if let Some(tick_func) = self.tick {
-				(tick_func)(self, articles);
+				(tick_func)(self, articles, grid, input);
 			}
- 		}
-
-		pub fn attach(&mut self, attachment: String,  articles: &mut HashMap<String, Article>) {
-			self.attached = Some(attachment);
-			if let Some(attachment) = articles.get_mut(self.attached.as_ref().unwrap()) {
-				if !attachment.attached_to.contains(self.attached.as_ref().unwrap()) {
-					attachment.attached_to.push(self.name.clone());
-				}
+			//Shown after the tick so a clip the tick switched to appears straight away
+			if let Some(frame) = self.components.animation.as_ref().and_then(|animation| animation.current_frame()) {
+				self.set_frame(frame);
 			}
-		}
+ 		}
 
 		pub fn remove_attachment(&mut self, articles: &mut HashMap<String, Article>) {
 			if let Some(attach_key) = &self.attached {
@@ -215,14 +378,34 @@ pub mod article {
 		}
 		
 		/**
-		 * Calculate leading edge of bounds and perform appropriate collisions as needed for each article
+		 * Never moves and has no behaviour of its own, like floor tiles.
+		 * The world never steps these, only other articles collide with them
 		 */
-		pub fn calculate_collisions(&mut self, articles: &mut HashMap<String, Article>) {
+		pub fn is_static(&self) -> bool {
+			self.mass.is_infinite() && self.tick.is_none() && self.do_collide.is_none() && self.post_physics.is_none() && self.draw.is_none()
+		}
 
-			if self.vel.abs().x < 0.05 {
+		/**
+		 * Smallest world space rect containing every bound
+		 */
+		pub fn bounding_box(&self) -> Option<Rect> {
+			self.bounds.iter().flatten()
+				.map(|bound| bound.offset(self.pos))
+				.chain(self.polygons.iter().flatten().map(|polygon| polygon.bounding_box().offset(self.pos)))
+				.reduce(|a, b| a.combine_with(b))
+		}
+
+		/**
+		 * Calculate leading edge of bounds and perform appropriate collisions as needed for each article.
+		 * Only articles the grid places near the moved bounds are tested
+		 */
+		pub fn calculate_collisions(&mut self, articles: &mut HashMap<String, Article>, grid: &SpatialGrid) {
+			let grounded_on = self.attached.clone();
+
+			if self.vel.abs().x < REST_SPEED {
 				self.vel.x = 0.0;
 			}
-			if self.vel.abs().y < 0.05 {
+			if self.vel.abs().y < REST_SPEED {
 				self.vel.y = 0.0;
 			}
 
@@ -230,80 +413,235 @@ pub mod article {
 				let mut did_collide: i8 = 1;
 				
 				while did_collide > 0 && did_collide < 20 {
-					match &self.bounds {
-						Some(bounds) => {
-							let collision = bounds.iter().fold(None, |collision: Option<Collision>, top_bound: &Rect| {
-								let delta = self.vel * axis;
-								if delta.cmpeq(Vec2::ZERO).all() {
-									return collision;
-								}
-								let delta_top_bound = top_bound.offset(delta).offset(self.pos);
-
-								articles.iter_mut()
-									.fold(collision, |collision: Option<Collision>, (bottom_index, bottom)| {
-									
-									match &bottom.bounds {
-										Some(bottom_bounds) => {
-											
-											bottom_bounds.into_iter().fold(collision, |collision, bottom_bound| {
-												match bottom_bound.offset(bottom.pos).intersect(delta_top_bound) {
-													
-													Some(intersection) => {
-														if (axis.x == 1.0 && intersection.h.abs() <= 2.0) 
-														|| (axis.y == 1.0 && intersection.w.abs() <= 2.0) {
-															return collision;
-														}
-														Collision {
-															intersection,
-															bottom_index: bottom_index.clone()
-														}.min_collision(axis, collision)
+					if let Some(bounds) = &self.bounds {
+						let delta = self.vel * axis * DT;
+						let candidates = match self.bounding_box() {
+							Some(area) if !delta.cmpeq(Vec2::ZERO).all() => grid.query(area.offset(delta)),
+							_ => Vec::new()
+						};
+						let collision = bounds.iter().enumerate().fold(None, |collision: Option<Collision>, (top_bound_index, top_bound)| {
+							let delta_top_bound = top_bound.offset(delta).offset(self.pos);
+							let top_one_way = self.one_way.contains(&top_bound_index);
+
+							candidates.iter()
+								.filter_map(|name| articles.get_key_value(*name))
+								.filter(|(_, bottom)| bottom.components.water.is_none())	//Water is felt through buoyancy, not bumped into
+								.fold(collision, |collision: Option<Collision>, (bottom_index, bottom)| {
+								
+								match &bottom.bounds {
+									Some(bottom_bounds) => {
+										
+										bottom_bounds.iter().enumerate().fold(collision, |collision, (bottom_bound_index, bottom_bound)| {
+											let top_rect = top_bound.offset(self.pos);
+											let bottom_rect = bottom_bound.offset(bottom.pos);
+											if top_one_way && (bottom.dropping_through.as_ref() == Some(&self.name)
+												|| !one_way_blocks(axis, bottom_rect, top_rect)) {
+												return collision;
+											}
+											if bottom.one_way.contains(&bottom_bound_index) && (self.dropping_through.as_ref() == Some(&bottom.name)
+												|| !one_way_blocks(axis, top_rect, bottom_rect)) {
+												return collision;
+											}
+											match bottom_rect.intersect(delta_top_bound) {
+												
+												Some(intersection) => {
+													if (axis.x == 1.0 && intersection.h.abs() <= 2.0) 
+													|| (axis.y == 1.0 && intersection.w.abs() <= 2.0) {
+														return collision;
 													}
-													None => collision
+													Collision {
+														intersection,
+														bottom_index: bottom_index.clone(),
+														top_material: self.material(top_bound_index),
+														bottom_material: bottom.material(bottom_bound_index),
+													}.min_collision(axis, collision)
 												}
-											})
-										},
-										None => collision	//If no bottom bounds maintain current collision
-									}
-								})
-							});
-							match collision {
-								Some(collision) => {
-									let bottom = articles.get_mut(&collision.bottom_index).unwrap();
-									
-									let collision_result = if let Some(collide_func) = self.do_collide {
-										collide_func(axis, self, bottom, &collision.intersection)
-									} else {
-										Self::default_collide(axis, self, bottom, &collision.intersection)
-									};
-									match collision_result {
-										CollisionResult::Continue => {
-											did_collide = 0;
-											if let Some(collide_func) = bottom.do_collide {
-												collide_func(axis, self, bottom, &collision.intersection);
-											} else {
-												Self::default_collide(axis, bottom, self, &collision.intersection);
+												None => collision
 											}
-										},
-										CollisionResult::DontPropagate(collide_count) => did_collide += collide_count
-									};
-									if axis.x == 1.0 {
-										self.vel.y *= self.friction_coefficient;
-									} else if axis.y == 1.0 {
-										self.vel.x *= self.friction_coefficient; //Friction
-									}
-								},
-								None => {
-									if axis.y == 0.0 && self.vel.abs().y > 0.0 && did_collide == 1 {
-										self.remove_attachment(articles);
+										})
+									},
+									None => collision	//If no bottom bounds maintain current collision
+								}
+							})
+						});
+						match collision {
+							Some(collision) => {
+								let bottom = articles.get_mut(&collision.bottom_index).unwrap();
+								let contact = Contact::between(collision.top_material, collision.bottom_material);
+								let impact = self.vel.dot(axis);
+								
+								let collision_result = if let Some(collide_func) = self.do_collide {
+									collide_func(axis, self, bottom, &collision.intersection)
+								} else if bottom.trigger {
+									CollisionResult::Continue	//Nothing to bounce off, the trigger's own collide reacts to the touch
+								} else if let Some(collide_func) = bottom.do_collide {
+									collide_func(axis, bottom, self, &collision.intersection)	//Handed over the way player_collide does, so spikes hurt whatever lands on them
+								} else {
+									Self::default_collide(axis, self, bottom, &collision.intersection)
+								};
+								match collision_result {
+									CollisionResult::Continue => {
+										did_collide = 0;
+										if let Some(collide_func) = bottom.do_collide {
+											collide_func(axis, self, bottom, &collision.intersection);
+										} else {
+											Self::default_collide(axis, bottom, self, &collision.intersection);
+										}
+									},
+									CollisionResult::DontPropagate(collide_count) => did_collide += collide_count
+								};
+								let friction = contact.friction(self.step_friction());
+								if axis.x == 1.0 {
+									self.vel.y *= friction;
+								} else if axis.y == 1.0 {
+									self.vel.x *= friction; //Friction
+									if impact > 0.0 {
+										self.footing = collision.bottom_material;
 									}
-									did_collide = 0;
 								}
+								if !bottom.trigger && (impact * contact.restitution).abs() > BOUNCE_SPEED {
+									self.vel -= axis * (self.vel.dot(axis) + impact * contact.restitution);
+								}
+							},
+							None => {
+								if axis.y == 0.0 && self.vel.abs().y > 0.0 && did_collide == 1 {
+									self.remove_attachment(articles);
+								}
+								did_collide = 0;
+							}
+						}
+					}
+				}
+				self.pos += self.vel * axis * DT;
+			}
+			if !self.collide_polygons(articles, grid) {
+				if let Some(name) = grounded_on {
+					self.snap_to_slope(&name, articles);
+				}
+			}
+
+			//Solid to it again once fallen clear
+			if let Some(name) = &self.dropping_through {
+				let clear = match (self.bounding_box(), articles.get(name).and_then(|other| other.bounding_box())) {
+					(Some(area), Some(other_area)) => area.intersect(other_area).is_none(),
+					_ => true
+				};
+				if clear {
+					self.dropping_through = None;
+				}
+			}
+		}
+
+		/**
+		 * Fraction of its speed kept over one step on the ground
+		 */
+		fn step_friction(&self) -> f32 {
+			self.friction_coefficient.powf(DT)
+		}
+
+		/**
+		 * Fall through the one way article this is standing on, false if it isn't standing on one
+		 */
+		pub fn drop_through(&mut self, articles: &mut HashMap<String, Article>) -> bool {
+			let Some(name) = self.attached.clone() else {
+				return false;
+			};
+			if articles.get(&name).is_none_or(|other| other.one_way.is_empty()) {
+				return false;
+			}
+			self.remove_attachment(articles);
+			self.dropping_through = Some(name);
+			true
+		}
+
+		/**
+		 * Push the article's bounds out of any polygons it ended up inside after moving.
+		 * Velocity into the surface is removed and the rest kept, so sliding carries on along a slope
+		 */
+		fn collide_polygons(&mut self, articles: &mut HashMap<String, Article>, grid: &SpatialGrid) -> bool {
+			let mut grounded = false;
+			let (Some(bounds), Some(area)) = (self.bounds.clone(), self.bounding_box()) else {
+				return grounded;
+			};
+			for name in grid.query(area) {
+				let Some(other) = articles.get_mut(name) else {
+					continue;
+				};
+				if other.trigger {
+					continue;
+				}
+				for (polygon_index, polygon) in other.polygons.iter().flatten().enumerate() {
+					let polygon = polygon.offset(other.pos);
+					let polygon_material = other.polygon_material(polygon_index);
+					for (bound_index, bound) in bounds.iter().enumerate() {
+						let Some(Penetration { normal, depth }) = polygon.penetration(&bound.offset(self.pos)) else {
+							continue;
+						};
+						let friction = Contact::between(self.material(bound_index), polygon_material).friction(self.step_friction());
+						let walkable = -normal.y > WALKABLE_NORMAL;
+						if walkable {
+							//Straight up rather than along the normal, so resting on a slope doesn't creep down it
+							self.pos.y -= depth / -normal.y;
+							self.footing = polygon_material;
+							grounded = true;
+							self.attached = Some(other.name.clone());
+							if !other.attached_to.contains(&self.name) {
+								other.attached_to.push(self.name.clone());
 							}
-						},
-						None => ()
+						} else {
+							self.pos += normal * depth;
+						}
+
+						let into = self.vel.dot(normal);
+						if into >= 0.0 {
+							continue;	//Already moving away, like at the start of a jump
+						}
+						let tangent = normal.perp();	//Points right along walkable surfaces
+						if walkable && friction < SLIPPERY_FRICTION.powf(DT) {
+							//Grip holds against gravity, only the article's own sideways speed carries it along the slope
+							let mut along = self.vel.x / tangent.x * friction;
+							if along.abs() < STICK_SPEED {
+								along = 0.0;
+							}
+							self.vel = tangent * along;
+						} else {
+							self.vel -= normal * into;
+							if walkable {
+								self.vel = tangent * self.vel.dot(tangent) * friction + normal * self.vel.dot(normal);
+							}
+						}
 					}
 				}
-				self.pos += self.vel * axis;
+			}
+			grounded
+		}
+
+		/**
+		 * Keep an article that was on a slope last step on it, when moving along the slope carried it
+		 * just clear of the surface. Without this, walking uphill leaves the ground every other step
+		 */
+		fn snap_to_slope(&mut self, name: &str, articles: &mut HashMap<String, Article>) {
+			let Some(other) = articles.get_mut(name) else {
+				return;
+			};
+			let Some(bounds) = &self.bounds else {
+				return;
+			};
+			let probe = self.pos + vec2(0.0, SNAP_DISTANCE);
+			let contact = other.polygons.iter().flatten()
+				.flat_map(|polygon| {
+					let polygon = polygon.offset(other.pos);
+					bounds.iter().filter_map(move |bound| polygon.penetration(&bound.offset(probe)))
+				})
+				.filter(|penetration| -penetration.normal.y > WALKABLE_NORMAL && self.vel.dot(penetration.normal) < STICK_SPEED)
+				.map(|penetration| penetration.depth / -penetration.normal.y)
+				.reduce(f32::max);
+			if let Some(lift) = contact {
+				self.pos.y = probe.y - lift;
+				self.attached = Some(other.name.clone());
+				if !other.attached_to.contains(&self.name) {
+					other.attached_to.push(self.name.clone());
+				}
 			}
 		}
 
@@ -332,17 +670,18 @@ pub mod article {
 		pub fn flat_collide(axis: Vec2, a: &mut Article, b: &mut Article, intersection: &Rect) -> CollisionResult {
 			if a.mass.is_finite() {
 				if axis.x == 1.0 {
+					//Change velocity by just enough to back out of the intersection this step
 					if a.vel.x <= 0.0 {
-						a.vel.x += intersection.w;
+						a.vel.x += intersection.w / DT;
 					} else {
-						a.vel.x -= intersection.w;
+						a.vel.x -= intersection.w / DT;
 					}
 				}
 				else if axis.y == 1.0 {
 					if a.pos.y > b.pos.y {
-						a.vel.y += intersection.h;
+						a.vel.y += intersection.h / DT;
 					} else {
-						a.vel.y -= intersection.h;
+						a.vel.y -= intersection.h / DT;
 					}
 				}
 				return CollisionResult::DontPropagate(-10);
@@ -400,7 +739,9 @@ pub mod article {
 	 */
 	pub struct Collision {
 		intersection: Rect,
-		bottom_index: String
+		bottom_index: String,
+		top_material: Option<Material>,
+		bottom_material: Option<Material>,
 	}
 
 	impl Collision {
@@ -432,13 +773,52 @@ pub mod article {
 		fn clone(&self) -> Self {
 			Collision {
 				intersection: self.intersection,
-				bottom_index: self.bottom_index.clone()
+				bottom_index: self.bottom_index.clone(),
+				top_material: self.top_material,
+				bottom_material: self.bottom_material,
 			}
 		}
 	}
 
+	/**
+	 * Notifications from an article to the game loop
+	 */
+	#[derive(Clone, Copy, PartialEq)]
+	pub enum Event {
+		LevelExit,
+		CheckpointReached(Vec2),	//Where the player should respawn from now on
+		Respawn,	//Player asked to go back to the last checkpoint
+		PlayerDied,	//Health ran out or the player fell out of the world
+		Splash(Vec2),	//An article crossed the surface of water here, going in or out
+	}
+
+	/**
+	 * How an article is handled once it has left the level's bounds
+	 */
+	#[derive(Clone, Copy, Default, PartialEq, Deserialize)]
+	pub enum OutOfBounds {
+		#[default]
+		Destroy,
+		Respawn,	//Back to where the level placed it, the player goes back to the last checkpoint
+		Damage(f32),	//Lose this much health then respawn
+		Kill,	//Health drops to nothing, articles without health are destroyed
+		Ignore,	//Left alone, for articles parked out of sight like an unused lure
+	}
+
+	/**
+	 * Which pass an article is drawn in. Back is drawn first, so Front covers everything else
+	 */
+	#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+	pub enum RenderLayer {
+		Back,	//Ground and scenery the action happens in front of
+		#[default]
+		Main,
+		Front,	//Decoration that passes in front of the player
+	}
+
 	pub enum CollisionResult {
 		DontPropagate(i8),
+		#[allow(dead_code)]
 		Continue
 	}
 }
\ No newline at end of file
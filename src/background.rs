@@ -0,0 +1,107 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
+pub mod background {
+	use macroquad::prelude::*;
+	use serde::Deserialize;
+	use std::collections::HashMap;
+
+	/**
+	 * One picture behind the articles, as written in a level file
+	 */
+	#[derive(Clone, Deserialize)]
+	pub struct Layer {
+		pub texture: String,
+		#[serde(default)]
+		pub parallax: f32,	//Pixels the layer moves for each pixel the camera does, 1 keeps up with the articles and 0 stays put on screen
+		#[serde(default)]
+		pub offset: (f32, f32),	//Top left of the layer while the camera target is at the world origin
+		#[serde(default)]
+		pub size: Option<(f32, f32)>,	//Drawn size of one tile, the texture's own size if left out
+		#[serde(default)]
+		pub tile_x: bool,
+		#[serde(default)]
+		pub tile_y: bool,
+		#[serde(default)]
+		pub scroll: (f32, f32),	//Pixels per second the layer drifts by itself, like clouds
+	}
+
+	impl Layer {
+		/**
+		 * Top left corner of every tile needed to cover the view, given the camera target and seconds since the game started
+		 */
+		pub fn tiles(&self, view: Rect, target: Vec2, time: f32, size: Vec2) -> Vec<Vec2> {
+			let origin = Vec2::from(self.offset) + target * (1.0 - self.parallax) + Vec2::from(self.scroll) * time;
+			let span = |tile: bool, origin: f32, size: f32, min: f32, max: f32| -> Vec<f32> {
+				if !tile || size <= 0.0 {
+					return vec![origin];
+				}
+				let first = origin + ((min - origin) / size).floor() * size;
+				let count = ((max - first) / size).ceil().max(1.0) as usize;
+				(0..count).map(|i| first + i as f32 * size).collect()
+			};
+			let xs = span(self.tile_x, origin.x, size.x, view.left(), view.right());
+			let ys = span(self.tile_y, origin.y, size.y, view.top(), view.bottom());
+			ys.iter().flat_map(|y| xs.iter().map(move |x| vec2(*x, *y))).collect()
+		}
+	}
+
+	/**
+	 * A level's layers with their textures, drawn back to front before any article
+	 */
+	#[derive(Default)]
+	pub struct Background {
+		layers: Vec<(Layer, Option<Texture2D>)>,
+	}
+
+	impl Background {
+		pub fn new(layers: &[Layer], textures: &HashMap<String, Texture2D>) -> Self {
+			Self {
+				layers: layers.iter().map(|layer| (layer.clone(), textures.get(&layer.texture).cloned())).collect(),
+			}
+		}
+
+		pub fn draw(&self, camera: &Camera2D, time: f32) {
+			let half = vec2(1.0 / camera.zoom.x.abs(), 1.0 / camera.zoom.y.abs());
+			let view = Rect::new(camera.target.x - half.x, camera.target.y - half.y, half.x * 2.0, half.y * 2.0);
+			for (layer, texture) in self.layers.iter() {
+				let Some(texture) = texture else {
+					continue;
+				};
+				let size = layer.size.map_or(texture.size(), Vec2::from);
+				for corner in layer.tiles(view, camera.target, time, size) {
+					draw_texture_ex(texture, corner.x, corner.y, WHITE, DrawTextureParams {
+						dest_size: Some(size),
+						..Default::default()
+					});
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::background::Layer;
+	use macroquad::math::{vec2, Rect};
+
+	fn clouds(parallax: f32) -> Layer {
+		Layer { texture: String::new(), parallax, offset: (0.0, -400.0), size: None, tile_x: true, tile_y: false, scroll: (10.0, 0.0) }
+	}
+
+	#[test]
+	fn tiles_cover_the_view_and_lag_behind_the_camera() {
+		let size = vec2(1000.0, 500.0);
+		let view = Rect::new(4500.0, -200.0, 2500.0, 1400.0);
+		let target = view.center();
+		let tiles = clouds(0.5).tiles(view, target, 2.0, size);
+		assert!(tiles.first().unwrap().x <= view.left());
+		assert!(tiles.last().unwrap().x + size.x >= view.right());
+		assert!(tiles.iter().all(|tile| tile.y == -400.0 + target.y * 0.5));
+
+		//Half as far across as the camera, plus the drift, so the tiling lines up on that point
+		let origin = target.x * 0.5 + 20.0;
+		assert!(tiles.iter().all(|tile| ((tile.x - origin) / size.x).fract().abs() < 1e-3));
+
+		let still = clouds(1.0).tiles(view, target, 0.0, size);
+		assert!(still.iter().all(|tile| (tile.x / size.x).fract().abs() < 1e-3 && tile.y == -400.0));
+	}
+}
@@ -0,0 +1,553 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
+pub mod behaviour {
+	use crate::article::article::{Article, CollisionResult, Event, TickFn, CollideFn, PostPhysicsFn, DrawFn};
+	use crate::broadphase::broadphase::SpatialGrid;
+	use crate::component::component::{CameraZoom, Controller, HookState, Hookable, LureState};
+	use crate::input::input::{Action, InputState};
+	use crate::world::world::DT;
+	use std::collections::HashMap;
+	use macroquad::prelude::*;
+
+	/**
+	 * Set of functions an article is bound to by the behaviour id given in the level file,
+	 * one for each phase of a step it takes part in, then one for drawing
+	 */
+	#[derive(Clone, Copy, Default)]
+	pub struct Behaviour {
+		pub tick: Option<TickFn>,
+		pub do_collide: Option<CollideFn>,
+		pub post_physics: Option<PostPhysicsFn>,
+		pub draw: Option<DrawFn>,
+	}
+
+	impl Behaviour {
+		pub fn bind(&self, article: &mut Article) {
+			article.tick = self.tick;
+			article.do_collide = self.do_collide;
+			article.post_physics = self.post_physics;
+			article.draw = self.draw;
+		}
+	}
+
+	/**
+	 * Every behaviour a level file can refer to, keyed by id
+	 */
+	pub fn registry() -> HashMap<String, Behaviour> {
+		HashMap::from([
+			("player".to_string(), Behaviour { tick: Some(player_tick), do_collide: Some(player_collide), post_physics: None, draw: None }),
+			("platform".to_string(), Behaviour { tick: Some(platform_tick), do_collide: Some(platform_collide), post_physics: None, draw: None }),
+			("block".to_string(), Behaviour { tick: None, do_collide: Some(block_collide), post_physics: None, draw: None }),
+			("spider".to_string(), Behaviour { tick: Some(spider_tick), do_collide: Some(spider_collide), post_physics: None, draw: None }),
+			("fisherman".to_string(), Behaviour { tick: Some(fisherman_tick), do_collide: Some(fisherman_collide), post_physics: Some(fisherman_post_physics), draw: None }),
+			("lure".to_string(), Behaviour { tick: Some(lure_tick), do_collide: Some(lure_collide), post_physics: None, draw: Some(lure_draw) }),
+			("exit".to_string(), Behaviour { tick: None, do_collide: Some(exit_collide), post_physics: None, draw: None }),
+			("checkpoint".to_string(), Behaviour { tick: None, do_collide: Some(checkpoint_collide), post_physics: None, draw: None }),
+			("hazard".to_string(), Behaviour { tick: None, do_collide: Some(hazard_collide), post_physics: None, draw: None }),
+		])
+	}
+
+
+	/**
+	 * Exits don't block anything, they only notify the game loop once the player walks into them
+	 */
+	fn exit_collide(_axis: Vec2, a: &mut Article, b: &mut Article, _intersection: &Rect) -> CollisionResult {
+		let (exit, other) = if a.components.exit.is_some() { (a, b) } else { (b, a) };
+		if other.name == "Player" && !exit.events.contains(&Event::LevelExit) {
+			exit.events.push(Event::LevelExit);
+		}
+		CollisionResult::DontPropagate(-10)
+	}
+
+	/**
+	 * Checkpoints don't block anything either, the first touch from the player moves the respawn point here
+	 */
+	fn checkpoint_collide(_axis: Vec2, a: &mut Article, b: &mut Article, _intersection: &Rect) -> CollisionResult {
+		let (checkpoint, other) = if a.components.checkpoint.is_some() { (a, b) } else { (b, a) };
+		if other.name == "Player" {
+			if let Some(state) = &mut checkpoint.components.checkpoint {
+				if !state.reached {
+					state.reached = true;
+					let spawn = checkpoint.pos + vec2(state.spawn.0, state.spawn.1);
+					checkpoint.events.push(Event::CheckpointReached(spawn));
+				}
+			}
+		}
+		CollisionResult::DontPropagate(-10)
+	}
+
+
+	/**
+	 * Hazards hurt and knock back anything with health that isn't still recovering from a hit.
+	 * Solid ones like spikes are stood on as usual, triggers like fire are passed through
+	 */
+	fn hazard_collide(axis: Vec2, a: &mut Article, b: &mut Article, intersection: &Rect) -> CollisionResult {
+		let (hazard, other) = if a.components.hazard.is_some() { (a, b) } else { (b, a) };
+		let collision_result = if hazard.trigger {
+			CollisionResult::DontPropagate(-10)
+		} else {
+			Article::default_collide(axis, hazard, other, intersection)
+		};
+		let vulnerable = other.components.health.as_ref().is_some_and(|health| !health.is_invulnerable());
+		if let (Some(rules), true) = (&hazard.components.hazard, vulnerable) {
+			other.update_health(-rules.damage);
+			let away = match (other.bounding_box(), hazard.bounding_box()) {
+				(Some(other_area), Some(hazard_area)) if other_area.center().x < hazard_area.center().x => -1.0,
+				_ => 1.0
+			};
+			if rules.knockback != (0.0, 0.0) {
+				other.vel = vec2(rules.knockback.0 * away, rules.knockback.1);
+				other.attached = None;	//Thrown clear rather than left standing on it
+				hazard.attached_to.retain(|name| *name != other.name);
+			}
+		}
+		collision_result
+	}
+
+
+	const WALK_FRICTION: f32 = 0.00006;	//Fraction of its speed kept after a second of standing, so it stops in a few steps
+	const SLIDE_FRICTION: f32 = 0.9994;	//Barely slows down on its belly
+	const IDLE_SPEED: f32 = 30.0;	//Pixels per second under which the player stands idle instead of walking
+	const SWIM_DEPTH: f32 = 0.5;	//Fraction of the player under water past which it swims even when standing on something
+
+	fn player_tick(player: &mut Article, articles: &mut HashMap<String, Article>, grid: &SpatialGrid, input: &InputState) {
+		let mut controller = player.components.controller.take().unwrap_or_default();
+		{	//Allow Zooming in and out of the screen
+			let zoom_steps = input.zoom();
+			if zoom_steps != 0.0 {
+				if let Some(camera) = &mut player.components.camera_zoom {
+					camera.zoom *= 1.1f32.powf(zoom_steps);
+				} else {
+					player.components.camera_zoom = Some(CameraZoom { zoom: 0.0008 });
+				}
+			}
+		}
+
+		if input.is_down(Action::Respawn) && !player.events.contains(&Event::Respawn) {
+			player.events.push(Event::Respawn);
+		}
+		if player.in_water.is_some() && (player.attached.is_none() || player.submerged > SWIM_DEPTH) {
+			player_swim(player, articles, grid, input, &mut controller);
+		} else {
+			player_walk(player, articles, grid, input, &mut controller);
+		}
+		player.components.controller = Some(controller);
+
+		//If player is hooked, they cannot move while the damage indicator blinks
+		if let Some(Hookable { state: HookState::Hooked }) = player.components.hookable {
+			player.vel = Vec2::ZERO;
+		}
+	}
+
+	/**
+	 * Walking, sliding and jumping on land or in the air
+	 */
+	fn player_walk(player: &mut Article, articles: &mut HashMap<String, Article>, grid: &SpatialGrid, input: &InputState, controller: &mut Controller) {
+		let dropping = input.is_down(Action::Down) && input.is_down(Action::Jump);	//Down shares its keys with slide by default
+		if input.is_down(Action::Slide) {
+			if !player.is_pose("sliding") && !dropping && player.set_pose("sliding", articles, grid) {
+				player.vel.x*=1.5;	//Dash once when on belly
+			}
+		} else if player.is_pose("sliding") {
+			player.set_pose("standing", articles, grid);	//Stays on its belly until there's room overhead
+		}
+		if player.is_pose("sliding") {
+			player.friction_coefficient = SLIDE_FRICTION;
+			player.play("slide");
+		} else {
+			player.friction_coefficient = WALK_FRICTION;
+			let traction = player.footing.map_or(1.0, |material| material.grip());	//Feet spin on ice
+
+			if input.is_down(Action::MoveLeft) {
+				if player.attached.is_some() {
+					player.vel.x -= controller.walk_accel * traction * DT;
+				} else if player.vel.x < 0.0 {
+					player.vel.x -= controller.air_accel * DT;
+				} else if player.vel.x >= 0.0 {
+					player.vel.x -= controller.walk_accel * DT;
+				}
+				player.set_direction(-Vec2::X);
+			}
+			else if input.is_down(Action::MoveRight) {
+				if player.attached.is_some() {
+					player.vel.x += controller.walk_accel * traction * DT;
+				} else if player.vel.x > 0.0 {
+					player.vel.x += controller.air_accel * DT;
+				} else if player.vel.x <= 0.0 {
+					player.vel.x += controller.walk_accel * DT;
+				}
+				player.set_direction(Vec2::X);
+			}
+		}
+		if !player.is_pose("sliding") {
+			if player.vel.x.abs() > IDLE_SPEED {
+				player.play("walk");
+			} else {
+				player.play("idle");
+			}
+		}
+		let jump_down = input.is_down(Action::Jump);
+		if jump_down && !controller.jump_held && input.is_down(Action::Down) && player.drop_through(articles) {
+			controller.cancel_jump();
+		} else {
+			player.vel.y = controller.jump(DT, player.attached.is_some(), jump_down, player.vel.y);
+		}
+	}
+
+	/**
+	 * Swimming takes over from walking and jumping while the player is mostly under water.
+	 * The penguin swims on its belly pushing off in any direction, jump gives a stroke upwards to leap out at the surface
+	 */
+	fn player_swim(player: &mut Article, articles: &mut HashMap<String, Article>, grid: &SpatialGrid, input: &InputState, controller: &mut Controller) {
+		let swimmer = player.components.swimmer.clone().unwrap_or_default();
+		if !player.is_pose("sliding") {
+			player.set_pose("sliding", articles, grid);
+		}
+		player.friction_coefficient = SLIDE_FRICTION;
+		player.play("slide");
+
+		let mut push = Vec2::ZERO;
+		if input.is_down(Action::MoveLeft) {
+			push.x -= 1.0;
+			player.set_direction(-Vec2::X);
+		} else if input.is_down(Action::MoveRight) {
+			push.x += 1.0;
+			player.set_direction(Vec2::X);
+		}
+		if input.is_down(Action::Down) {
+			push.y += 1.0;
+		}
+		player.vel += push * swimmer.accel * DT;
+
+		let jump_down = input.is_down(Action::Jump);
+		if jump_down && !controller.jump_held {
+			player.vel.y = player.vel.y.min(-swimmer.stroke_speed);
+		}
+		controller.cancel_jump();	//No walking jump left over for when it comes out of the water
+		controller.jump_held = jump_down;
+	}
+
+	fn player_collide(axis: Vec2, a: &mut Article, b: &mut Article, intersection: &Rect) -> CollisionResult {
+		if axis.x == 1.0 {
+			if let Some(attachment) = &a.attached {
+				if attachment.as_str() == b.name.as_str() {
+					return CollisionResult::DontPropagate(-10);
+				}
+			}
+		}
+		match b.do_collide {
+			Some(collide_func) => collide_func(axis, b, a, intersection),
+			None => Article::default_collide(axis, a, b, intersection)
+		}
+	}
+
+
+	const PLATFORM_FALL_ACCEL: f32 = 7200.0;	//Pixels per second squared
+	const PLATFORM_RISE_SPEED: f32 = 180.0;	//Pixels per second
+	const PLATFORM_TURN_SPEED: f32 = 60.0;	//Pixels per second, set when bouncing off the top
+
+	fn platform_tick(platform: &mut Article, articles: &mut HashMap<String, Article>, _grid: &SpatialGrid, _input: &InputState) {
+		platform.remove_attachment(articles);
+		platform.vel.x = 0.0;
+		if platform.vel.y >= 0.0 {
+			platform.vel.y += PLATFORM_FALL_ACCEL * DT;
+			platform.attached_to = vec![];
+		} else {
+			platform.vel.y = -PLATFORM_RISE_SPEED;
+		}
+		if -100.0 > platform.pos.y {
+			platform.pos.y = -100.0;
+			platform.vel.y = PLATFORM_TURN_SPEED;
+		}
+		if platform.pos.y > 400.0 {
+			platform.pos.y = 400.0;
+			platform.vel.y = -PLATFORM_RISE_SPEED;
+		}
+	}
+
+	/**
+	 * Platform bounds are one way, so whatever a platform collides with is on top of it
+	 */
+	fn platform_collide(axis: Vec2, a: &mut Article, b: &mut Article, intersection: &Rect) -> CollisionResult {
+		let carrying = !a.one_way.is_empty();	//The platform is the one with one way bounds, whatever it's called
+		if carrying {
+			b.vel.y -= a.vel.y;	//Carried up as the platform rises into it
+		}
+		let collision_result = Article::flat_collide(axis, b, a, intersection);
+		if carrying {
+			b.attached = Some(a.name.clone());
+			if !a.attached_to.contains(&b.name) {
+				a.attached_to.push(b.name.clone());
+			}
+		}
+		collision_result
+	}
+
+
+	fn block_collide(axis: Vec2, a: &mut Article, b: &mut Article, intersection: &Rect) -> CollisionResult {
+		if axis.x == 1.0 {
+			if let Some(attachment) = &a.attached {
+				if attachment.as_str() == b.name.as_str() {
+					return CollisionResult::DontPropagate(-10);
+				}
+			}
+			return Article::elastic_collide(axis, a, b, intersection);
+		}
+		if a.pos.y < b.pos.y {
+			a.attached = Some(b.name.clone());
+			Article::flat_collide(axis, a, b, intersection)
+		} else {
+			b.attached = Some(a.name.clone());
+			Article::flat_collide(axis, b, a, intersection)
+		}
+	}
+
+
+	const SPIDER_SPEED: f32 = 360.0;	//Pixels per second
+	const SPIDER_TURN_SPEED: f32 = 300.0;	//Pixels per second after reaching the end of the patrol
+	const STOMP_SPEED: f32 = 6.0;	//Falling faster than this onto an enemy counts as landing on it
+
+	fn spider_tick(enemy: &mut Article, _articles: &mut HashMap<String, Article>, _grid: &SpatialGrid, _input: &InputState) {
+		if enemy.vel.x >= 0.0 {
+			enemy.vel.x = SPIDER_SPEED;
+			enemy.set_direction(Vec2::X);
+		} else {
+			enemy.vel.x = -SPIDER_SPEED;
+			enemy.set_direction(-Vec2::X);
+		}
+		enemy.play("walk");
+		if let Some(patrol) = &enemy.components.patrol {
+			if patrol.origin_x - patrol.left > enemy.pos.x {
+				enemy.pos.x = patrol.origin_x - patrol.left;
+				enemy.vel.x = SPIDER_TURN_SPEED;
+			}
+			if enemy.pos.x > patrol.origin_x + patrol.right {
+				enemy.pos.x = patrol.origin_x + patrol.right;
+				enemy.vel.x = -SPIDER_TURN_SPEED;
+			}
+		}
+	}
+
+	fn spider_collide(axis: Vec2, a: &mut Article, b: &mut Article, intersection: &Rect) -> CollisionResult {
+		if axis.y == 1.0 && b.vel.y > STOMP_SPEED {
+			a.do_destroy = true;
+		} else if b.name.contains("Player") {
+			b.update_health(-1.0);
+		}
+		let collision_result = Article::default_collide(axis, a, b, intersection);
+		if axis.x == 1.0 {
+			a.vel.x *= -1.0;
+		}
+		collision_result
+	}
+
+
+	fn lure_collide(axis: Vec2, a: &mut Article, b: &mut Article, intersection: &Rect) -> CollisionResult {
+		if a.name.contains("lure") {
+			if let Some(lure) = &a.components.lure {
+				if !lure.hidden {
+					if lure.state != LureState::Reeling {
+						let player_free = matches!(&b.components.hookable, Some(hookable) if hookable.state == HookState::Free);
+						if b.name.contains("Player") && player_free {
+							//Hurt player and mark both as hooked
+							b.update_health(-1.0);
+							if let Some(hookable) = &mut b.components.hookable {
+								hookable.state = HookState::JustHooked;
+							}
+							set_lure_state(a, LureState::Landed);
+						}
+
+						if axis == Vec2::Y {
+							//Mark hook as landed on something
+							set_lure_state(a, LureState::Landed);
+						}
+					} else if b.name.contains("Player") {
+						return CollisionResult::DontPropagate(10);
+					}
+					return Article::elastic_collide(axis, a, b, intersection);
+				}
+			}
+		}
+		CollisionResult::DontPropagate(10)
+	}
+
+	fn set_lure_state(lure: &mut Article, state: LureState) {
+		if let Some(lure) = &mut lure.components.lure {
+			lure.state = state;
+		}
+	}
+
+	fn lure_draw(lure: &mut Article) -> bool {
+		if let Some(line) = &lure.components.lure {
+			if !line.hidden {
+				let pole = line.pole.unwrap_or(lure.pos);
+				draw_line(pole.x, pole.y, lure.pos.x+5.0, lure.pos.y+5.0, 1.0, BLACK);
+				return true;
+			}
+		}
+		false
+	}
+
+	fn lure_tick(lure: &mut Article, articles: &mut HashMap<String, Article>, _grid: &SpatialGrid, _input: &InputState) {
+		let free = matches!(&lure.components.lure, Some(line) if line.state == LureState::Free);
+		if free {
+			if let Some(player) = articles.get_mut("Player") {
+				if (player.pos.x - lure.pos.x).abs() < 300.0 && lure.vel.abs().x > 60.0 {
+					//Need to calculate lure distance and remaining distance covered per step
+					lure.vel.x *= 0.9 + (player.pos.x - lure.pos.x).abs() / (300.0 * lure.vel.abs().x * DT);
+				}
+			}
+		}
+	}
+
+
+	/**
+	 * Fisherman timings, in seconds since the fisherman spotted the player
+	 */
+	const CAST_AIM: f32 = 1.5;	//Remember where the player is standing, the lure is let go when the cast clip finishes
+	const CAST_RESTART: f32 = 5.0;	//Give up on a missed cast and start again
+	//Once something is hooked the status jumps here, well past any cast timing
+	const REEL_START: f32 = 100.0;
+	const REEL_YEET: f32 = REEL_START + 0.85;	//Damage indication is finished, reel the lure back in
+	const REEL_END: f32 = REEL_START + 2.0;
+	const LURE_THROW_SPEED: f32 = 900.0;	//Pixels per second upward
+	const LURE_FLIGHT_SECONDS: f32 = 0.85;	//Time for the lure to cover the distance to the player
+	const LURE_REEL_SPEED: f32 = 600.0;	//Pixels per second
+	const YEET_SPEED: f32 = 1200.0;	//Pixels per second upward when the player is reeled in
+
+	/**
+	 * True when a timer running from prev to now has just passed the mark
+	 */
+	fn crossed(prev: f32, now: f32, mark: f32) -> bool {
+		prev < mark && mark <= now
+	}
+
+	fn fisherman_tick(fisherman: &mut Article, articles: &mut HashMap<String, Article>, _grid: &SpatialGrid, _input: &InputState) {
+		let mut player_hooked = false;
+		if let Some(mut fishing) = fisherman.components.fishing.take() {
+			let mut status = fishing.status;
+			let mut prev_status = fishing.status;
+			if let Some(player) = articles.get_mut("Player") {
+				let pos = player.pos;
+				if let Some(hookable) = &mut player.components.hookable {
+					player_hooked = hookable.state == HookState::Hooked;
+					//JustHooked notifies to restart the reeling animation
+					if hookable.state == HookState::JustHooked {
+						hookable.state = HookState::Hooked;
+						status = REEL_START;
+						prev_status = REEL_START;
+						if let Some(animation) = &mut fisherman.components.animation {
+							animation.restart("reel");
+						}
+					}
+					if pos.distance(fisherman.pos) >= 1800.0 {
+						hookable.state = HookState::Free;
+					}
+				}
+				if player.pos.distance(fisherman.pos) < 1800.0 {
+					if status == 0.0 {
+						fisherman.play("cast");
+					}
+					status += DT;
+					if crossed(prev_status, status, CAST_AIM) {
+						//Start Casting
+						fishing.target = player.pos;
+					}
+				} else {
+					status = 0.0;
+					prev_status = 0.0;
+				}
+				if crossed(prev_status, status, REEL_YEET) && player_hooked {
+					//Reeled in and yeeted
+					player.vel.y -= YEET_SPEED;
+					if let Some(hookable) = &mut player.components.hookable {
+						hookable.state = HookState::Free;
+					}
+				}
+			}
+
+			let lure_name = format!("lure-{}", fisherman.name.clone());
+			if let Some(lure) = articles.get_mut(lure_name.as_str()) {
+				if let Some(mut line) = lure.components.lure.take() {
+					if line.state != LureState::Free {
+						if player_hooked {
+							fisherman.play("reel");
+						}
+
+						if status < REEL_START {
+							status = REEL_START;
+						}
+
+						let pole_tip = fisherman.anchor("pole_tip").unwrap_or(fisherman.pos);
+
+
+						if status > REEL_YEET {
+							if !player_hooked  {
+								fisherman.play("reel");
+							} else {
+								line.state = LureState::Reeling;
+							}
+							lure.vel = (pole_tip - lure.pos).normalize() * LURE_REEL_SPEED;
+						} else {
+							lure.vel = Vec2::ZERO;//Do not move lure until damage indication is finished
+						}
+						if status > REEL_END {
+							status = 0.0;
+							fisherman.play("idle");
+							line.state = LureState::Free;
+							line.hidden = true;
+						}
+					} else if fisherman.animation_finished("cast") {
+						//Let go of the lure from the end of the pole
+						let pole_tip = fisherman.anchor("pole_tip").unwrap_or(fisherman.pos);
+						lure.pos = pole_tip;
+						lure.prev_pos = lure.pos;
+						lure.vel.y = -LURE_THROW_SPEED;
+						//Calculate x vel to run into player
+						lure.vel.x = (fishing.target.x - lure.pos.x) / LURE_FLIGHT_SECONDS;
+						line.pole = Some(pole_tip);
+						line.hidden = false;
+					} else if status > CAST_RESTART {
+						status = 0.0;	//Restart animation at start
+						fisherman.play("idle");
+						line.hidden = true;
+					} else if status == 0.0 {
+						//Reset lure
+						line.hidden = true;
+						fisherman.play("idle");
+					}
+					lure.components.lure = Some(line);
+				}
+			}
+			fishing.status = status;
+			fisherman.components.fishing = Some(fishing);
+		}
+	}
+
+	/**
+	 * The line is tied to the pole once everything has moved, so it never trails a step behind
+	 */
+	fn fisherman_post_physics(fisherman: &mut Article, articles: &mut HashMap<String, Article>) {
+		let pole_tip = fisherman.anchor("pole_tip").unwrap_or(fisherman.pos);
+		let lure_name = format!("lure-{}", fisherman.name);
+		if let Some(line) = articles.get_mut(lure_name.as_str()).and_then(|lure| lure.components.lure.as_mut()) {
+			if !line.hidden {
+				line.pole = Some(pole_tip);
+			}
+		}
+	}
+
+	fn fisherman_collide(axis: Vec2, a: &mut Article, b: &mut Article, intersection: &Rect) -> CollisionResult {
+		if axis.y == 1.0 && b.vel.y > STOMP_SPEED {
+			a.vel.y -= 900.0;
+		}
+
+		let collision_result = Article::default_collide(axis, a, b, intersection);
+		if axis.y == 1.0 && b.vel.y > STOMP_SPEED {
+			a.vel.y += 900.0;
+		}
+		if axis.x == 1.0 {
+			a.vel.x *= -1.0;
+		}
+		collision_result
+	}
+}
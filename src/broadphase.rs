@@ -0,0 +1,117 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
+pub mod broadphase {
+	use std::collections::HashMap;
+	use macroquad::math::Rect;
+
+	type CellRange = (i32, i32, i32, i32);	//Inclusive min x, min y, max x, max y cell coordinates
+
+	/**
+	 * Uniform grid of article names, used to find which articles are near enough to collide
+	 * without testing every pair
+	 */
+	pub struct SpatialGrid {
+		cell_size: f32,
+		cells: HashMap<(i32, i32), Vec<String>>,
+		entries: HashMap<String, CellRange>,
+	}
+
+	impl SpatialGrid {
+		pub fn new(cell_size: f32) -> Self {
+			Self {
+				cell_size,
+				cells: HashMap::new(),
+				entries: HashMap::new(),
+			}
+		}
+
+		fn cell_range(&self, area: &Rect) -> CellRange {
+			(
+				(area.x / self.cell_size).floor() as i32,
+				(area.y / self.cell_size).floor() as i32,
+				((area.x + area.w) / self.cell_size).floor() as i32,
+				((area.y + area.h) / self.cell_size).floor() as i32,
+			)
+		}
+
+		/**
+		 * Place an article in every cell its area touches, moving it if it was already in the grid.
+		 * Articles that stay within the same cells aren't touched
+		 */
+		pub fn update(&mut self, name: &str, area: Rect) {
+			let range = self.cell_range(&area);
+			if self.entries.get(name) == Some(&range) {
+				return;
+			}
+			self.remove(name);
+			for x in range.0..=range.2 {
+				for y in range.1..=range.3 {
+					self.cells.entry((x, y)).or_default().push(name.to_string());
+				}
+			}
+			self.entries.insert(name.to_string(), range);
+		}
+
+		pub fn remove(&mut self, name: &str) {
+			if let Some(range) = self.entries.remove(name) {
+				for x in range.0..=range.2 {
+					for y in range.1..=range.3 {
+						if let Some(cell) = self.cells.get_mut(&(x, y)) {
+							cell.retain(|entry| entry != name);
+							if cell.is_empty() {
+								self.cells.remove(&(x, y));
+							}
+						}
+					}
+				}
+			}
+		}
+
+		/**
+		 * Names of every article sharing a cell with the area, sorted so results don't depend on hashing
+		 */
+		pub fn query(&self, area: Rect) -> Vec<&str> {
+			let range = self.cell_range(&area);
+			let mut found = Vec::<&str>::new();
+			for x in range.0..=range.2 {
+				for y in range.1..=range.3 {
+					if let Some(cell) = self.cells.get(&(x, y)) {
+						found.extend(cell.iter().map(|name| name.as_str()));
+					}
+				}
+			}
+			found.sort_unstable();
+			found.dedup();
+			found
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::broadphase::SpatialGrid;
+	use macroquad::math::Rect;
+
+	#[test]
+	fn query_finds_only_nearby_entries() {
+		let mut grid = SpatialGrid::new(100.0);
+		grid.update("near", Rect::new(10.0, 10.0, 20.0, 20.0));
+		grid.update("far", Rect::new(1000.0, 1000.0, 20.0, 20.0));
+		grid.update("wide", Rect::new(-500.0, 50.0, 2000.0, 10.0));
+
+		assert_eq!(grid.query(Rect::new(0.0, 0.0, 50.0, 50.0)), vec!["near", "wide"]);
+		assert_eq!(grid.query(Rect::new(1005.0, 1005.0, 5.0, 5.0)), vec!["far"]);
+	}
+
+	#[test]
+	fn moved_and_removed_entries_leave_old_cells() {
+		let mut grid = SpatialGrid::new(100.0);
+		grid.update("crate", Rect::new(10.0, 10.0, 20.0, 20.0));
+		grid.update("crate", Rect::new(510.0, 10.0, 20.0, 20.0));
+
+		assert!(grid.query(Rect::new(0.0, 0.0, 50.0, 50.0)).is_empty());
+		assert_eq!(grid.query(Rect::new(500.0, 0.0, 50.0, 50.0)), vec!["crate"]);
+
+		grid.remove("crate");
+		assert!(grid.query(Rect::new(500.0, 0.0, 50.0, 50.0)).is_empty());
+	}
+}
@@ -0,0 +1,493 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
+pub mod component {
+	use macroquad::math::Vec2;
+	use serde::{Deserialize, Serialize};
+	use std::collections::HashMap;
+
+	/**
+	 * Typed per-article state. Behaviours reach for the component they need
+	 * and get None when the article doesn't have it
+	 * Fields the simulation changes are written to save files too, level files leave them out
+	 */
+	#[derive(Clone, Default, Deserialize, Serialize)]
+	#[serde(default)]
+	pub struct Components {
+		pub health: Option<Health>,
+		pub hookable: Option<Hookable>,
+		pub patrol: Option<Patrol>,
+		pub fishing: Option<FishingAI>,
+		pub lure: Option<Lure>,
+		pub camera_zoom: Option<CameraZoom>,
+		pub animation: Option<Animation>,
+		pub checkpoint: Option<Checkpoint>,
+		pub exit: Option<Exit>,
+		pub hazard: Option<Hazard>,
+		pub controller: Option<Controller>,
+		pub poses: Option<Poses>,
+		pub water: Option<Water>,
+		pub swimmer: Option<Swimmer>,
+	}
+
+	#[derive(Clone, Deserialize, Serialize)]
+	pub struct Health {
+		pub current: f32,
+		pub max: f32,
+		#[serde(default = "default_invulnerable_seconds")]
+		pub invulnerable_seconds: f32,	//How long after a hit further damage is ignored
+		#[serde(default)]
+		pub invulnerable_for: f32,	//Seconds of invulnerability left, counted down by the world step
+	}
+
+	fn default_invulnerable_seconds() -> f32 {
+		2.0
+	}
+
+	const BLINK_SECONDS: f32 = 0.1;	//Length of each on or off phase of the damage indicator
+
+	impl Health {
+		pub fn is_invulnerable(&self) -> bool {
+			self.invulnerable_for > 0.0
+		}
+
+		/**
+		 * Advance the invulnerability timer by one step of dt seconds
+		 */
+		pub fn cool_down(&mut self, dt: f32) {
+			self.invulnerable_for -= dt;
+			if self.invulnerable_for < dt / 2.0 {
+				self.invulnerable_for = 0.0;	//Don't let rounding from repeated steps add a step
+			}
+		}
+
+		/**
+		 * The damage indicator hides the article every other blink while invulnerable
+		 */
+		pub fn blinked_out(&self) -> bool {
+			self.is_invulnerable() && (self.invulnerable_for / BLINK_SECONDS) as i32 % 2 == 1
+		}
+	}
+
+	#[derive(Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+	pub enum HookState {
+		#[default]
+		Free,
+		JustHooked,	//Caught this step, tells the fisherman to restart the reeling animation
+		Hooked,	//Held in place until reeled in
+	}
+
+	/**
+	 * Can be caught by a fisherman's lure
+	 */
+	#[derive(Clone, Default, Deserialize, Serialize)]
+	pub struct Hookable {
+		#[serde(default)]
+		pub state: HookState,
+	}
+
+	/**
+	 * Walk back and forth around an origin, turning around at either end
+	 */
+	#[derive(Clone, Deserialize, Serialize)]
+	pub struct Patrol {
+		pub origin_x: f32,
+		pub left: f32,	//Distance from origin to turn around on the left
+		pub right: f32,	//Distance from origin to turn around on the right
+	}
+
+	#[derive(Clone, Default, Deserialize, Serialize)]
+	pub struct FishingAI {
+		#[serde(default)]
+		pub status: f32,	//Seconds into the current cast or reel
+		#[serde(default)]
+		pub target: Vec2,	//Where the player stood when the cast was aimed
+	}
+
+	#[derive(Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+	pub enum LureState {
+		#[default]
+		Free,
+		Landed,	//Hit the ground or the player, waiting to be reeled in
+		Reeling,	//Being pulled back with the player on the line
+	}
+
+	#[derive(Clone, Deserialize, Serialize)]
+	pub struct Lure {
+		#[serde(default)]
+		pub state: LureState,
+		#[serde(default = "hidden_by_default")]
+		pub hidden: bool,
+		#[serde(default)]
+		pub pole: Option<Vec2>,	//World position of the tip of the fishing pole the line hangs from
+	}
+
+	fn hidden_by_default() -> bool {
+		true
+	}
+
+	#[derive(Clone, Deserialize, Serialize)]
+	pub struct CameraZoom {
+		pub zoom: f32,
+	}
+
+	/**
+	 * Records a respawn point when the player touches it
+	 */
+	#[derive(Clone, Default, Deserialize, Serialize)]
+	pub struct Checkpoint {
+		#[serde(default)]
+		pub spawn: (f32, f32),	//Offset from the checkpoint's position the player respawns at
+		#[serde(default)]
+		pub reached: bool,
+	}
+
+	/**
+	 * Finishes the level when the player touches it
+	 */
+	#[derive(Clone, Default, Deserialize, Serialize)]
+	pub struct Exit;
+
+	/**
+	 * Hurts anything with health that touches the article, like spikes or fire
+	 */
+	#[derive(Clone, Deserialize, Serialize)]
+	pub struct Hazard {
+		pub damage: f32,
+		#[serde(default)]
+		pub knockback: (f32, f32),	//Pixels per second given to whatever got hurt, x is turned to point away from the hazard
+	}
+
+	/**
+	 * Platformer movement tuning and the timers it runs on. Every parameter has a default,
+	 * so a level only needs to list the ones it changes
+	 */
+	#[derive(Clone, Deserialize, Serialize)]
+	#[serde(default)]
+	pub struct Controller {
+		pub walk_accel: f32,	//Pixels per second squared while grounded or turning around
+		pub air_accel: f32,	//Pixels per second squared when already moving that way in the air
+		pub jump_speed: f32,	//Pixels per second upwards at the start of a jump
+		pub release_cut: f32,	//Fraction of upward speed kept when jump is let go early
+		pub coyote_seconds: f32,	//How long after walking off a ledge a jump is still allowed
+		pub buffer_seconds: f32,	//How long a jump pressed before landing is remembered
+		pub coyote_for: f32,
+		pub buffered_for: f32,
+		pub jump_held: bool,	//Jump was down last step, so holding it doesn't jump again
+		pub rising: bool,	//In a jump that can still be cut short
+	}
+
+	impl Default for Controller {
+		fn default() -> Self {
+			Self {
+				walk_accel: 7200.0,
+				air_accel: 720.0,
+				jump_speed: 1380.0,
+				release_cut: 0.5,
+				coyote_seconds: 0.1,
+				buffer_seconds: 0.1,
+				coyote_for: 0.0,
+				buffered_for: 0.0,
+				jump_held: false,
+				rising: false,
+			}
+		}
+	}
+
+	impl Controller {
+		/**
+		 * Use up a jump press for something else, so it doesn't also jump now or once it lands
+		 */
+		pub fn cancel_jump(&mut self) {
+			self.buffered_for = 0.0;
+			self.coyote_for = 0.0;
+			self.jump_held = true;
+		}
+
+		/**
+		 * Run the jump timers for one step of dt seconds, returning the new vertical velocity
+		 */
+		pub fn jump(&mut self, dt: f32, grounded: bool, jump_down: bool, vel_y: f32) -> f32 {
+			if grounded {
+				self.coyote_for = self.coyote_seconds;
+			} else {
+				self.coyote_for = (self.coyote_for - dt).max(0.0);
+			}
+			if jump_down && !self.jump_held {
+				self.buffered_for = self.buffer_seconds;
+			} else {
+				self.buffered_for = (self.buffered_for - dt).max(0.0);
+			}
+			self.jump_held = jump_down;
+
+			if self.buffered_for > 0.0 && self.coyote_for > 0.0 {
+				self.buffered_for = 0.0;
+				self.coyote_for = 0.0;	//No second jump from the same ledge
+				self.rising = true;
+				return -self.jump_speed;
+			}
+			if vel_y >= 0.0 {
+				self.rising = false;
+			} else if self.rising && !jump_down {
+				self.rising = false;
+				return vel_y * self.release_cut;
+			}
+			vel_y
+		}
+	}
+
+	/**
+	 * Collision bounds for one way of holding the body, in the same unflipped
+	 * article space as the bounds in a level file
+	 */
+	#[derive(Clone, Deserialize, Serialize)]
+	pub struct Pose {
+		pub bounds: Vec<(f32, f32, f32, f32)>,
+		#[serde(default)]
+		pub rotation: f32,	//Radians the sprite is turned while in this pose
+	}
+
+	/**
+	 * Named bounds sets an article switches between, like standing and sliding
+	 */
+	#[derive(Clone, Default, Deserialize, Serialize)]
+	pub struct Poses {
+		pub sets: HashMap<String, Pose>,
+		#[serde(default)]
+		pub current: String,
+	}
+
+	/**
+	 * A body of water. Whatever overlaps its bounds is held up and slowed down, the lighter it is the more so
+	 */
+	#[derive(Clone, Deserialize, Serialize)]
+	#[serde(default)]
+	pub struct Water {
+		pub density: f32,	//Mass of each square pixel of water pushed aside, compared with the article's own mass
+		pub drag: f32,	//Fraction of velocity lost per second by a mass of 1 fully under water
+	}
+
+	impl Default for Water {
+		fn default() -> Self {
+			Self {
+				density: 0.002,
+				drag: 60.0,
+			}
+		}
+	}
+
+	/**
+	 * Movement tuning used instead of the controller while under water
+	 */
+	#[derive(Clone, Deserialize, Serialize)]
+	#[serde(default)]
+	pub struct Swimmer {
+		pub accel: f32,	//Pixels per second squared in any direction
+		pub stroke_speed: f32,	//Pixels per second upwards from pressing jump, enough to leap out at the surface
+	}
+
+	impl Default for Swimmer {
+		fn default() -> Self {
+			Self {
+				accel: 2400.0,
+				stroke_speed: 600.0,
+			}
+		}
+	}
+
+	/**
+	 * A run of frames along one row of a spritesheet. Frames are counted in source sized cells
+	 */
+	#[derive(Clone, Deserialize, Serialize)]
+	pub struct Clip {
+		#[serde(default)]
+		pub row: u32,
+		#[serde(default)]
+		pub first: u32,
+		#[serde(default = "one_frame")]
+		pub count: u32,
+		pub seconds: Vec<f32>,	//Time each frame is shown for, the last entry is reused for any frames after it
+		#[serde(default)]
+		pub looping: bool,	//One-shot clips hold their last frame once finished
+	}
+
+	fn one_frame() -> u32 {
+		1
+	}
+
+	impl Clip {
+		fn frame_seconds(&self, frame: u32) -> f32 {
+			match self.seconds.get(frame as usize).or(self.seconds.last()) {
+				Some(seconds) => *seconds,
+				None => 0.0
+			}
+		}
+	}
+
+	/**
+	 * Named clips and which one is playing. The article advances it every step
+	 */
+	#[derive(Clone, Default, Deserialize, Serialize)]
+	pub struct Animation {
+		pub clips: HashMap<String, Clip>,
+		#[serde(default)]
+		playing: String,
+		#[serde(default)]
+		frame: u32,
+		#[serde(default)]
+		elapsed: f32,	//Seconds the current frame has been shown for
+		#[serde(default)]
+		complete: bool,
+		#[serde(default)]
+		finished: Option<String>,	//One-shot clip that completed during the last advance
+	}
+
+	impl Animation {
+		/**
+		 * Switch to a clip, carrying on where it was if it is already playing
+		 */
+		pub fn play(&mut self, clip: &str) {
+			if self.playing != clip {
+				self.restart(clip);
+			}
+		}
+
+		/**
+		 * Play a clip from its first frame
+		 */
+		pub fn restart(&mut self, clip: &str) {
+			self.playing = clip.to_string();
+			self.frame = 0;
+			self.elapsed = 0.0;
+			self.complete = false;
+		}
+
+		#[cfg(test)]
+		pub fn playing(&self) -> &str {
+			&self.playing
+		}
+
+		/**
+		 * True for the step a one-shot clip reached the end of its last frame
+		 */
+		pub fn just_finished(&self, clip: &str) -> bool {
+			self.finished.as_deref() == Some(clip)
+		}
+
+		pub fn advance(&mut self, dt: f32) {
+			self.finished = None;
+			let clip = match self.clips.get(&self.playing) {
+				Some(clip) => clip,
+				None => return
+			};
+			if self.complete {
+				return;
+			}
+			self.elapsed += dt;
+			loop {
+				let seconds = clip.frame_seconds(self.frame);
+				if seconds <= 0.0 || self.elapsed < seconds {
+					break;
+				}
+				if self.frame + 1 < clip.count {
+					self.frame += 1;
+				} else if clip.looping {
+					self.frame = 0;
+				} else {
+					self.complete = true;
+					self.finished = Some(self.playing.clone());
+					break;
+				}
+				self.elapsed -= seconds;
+			}
+		}
+
+		/**
+		 * Column and row of the spritesheet cell to show
+		 */
+		pub fn current_frame(&self) -> Option<Vec2> {
+			let clip = self.clips.get(&self.playing)?;
+			Some(Vec2::new((clip.first + self.frame) as f32, clip.row as f32))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::component::{Animation, Clip, Controller};
+	use macroquad::math::vec2;
+
+	fn animation() -> Animation {
+		let mut animation = Animation::default();
+		animation.clips.insert("cast".to_string(), Clip { row: 0, first: 0, count: 3, seconds: vec![0.5, 0.25], looping: false });
+		animation.clips.insert("reel".to_string(), Clip { row: 1, first: 4, count: 2, seconds: vec![0.25], looping: true });
+		animation
+	}
+
+	#[test]
+	fn one_shot_clip_finishes_once_and_holds_its_last_frame() {
+		let mut animation = animation();
+		animation.play("cast");
+		animation.advance(0.4);
+		assert_eq!(animation.current_frame(), Some(vec2(0.0, 0.0)));
+		animation.advance(0.2);
+		assert_eq!(animation.current_frame(), Some(vec2(1.0, 0.0)));
+		//The last duration carries on to frames after it
+		animation.advance(0.25);
+		assert_eq!(animation.current_frame(), Some(vec2(2.0, 0.0)));
+		assert!(!animation.just_finished("cast"));
+
+		animation.advance(0.2);
+		assert!(animation.just_finished("cast"));
+		animation.advance(1.0);
+		assert!(!animation.just_finished("cast"));
+		assert_eq!(animation.current_frame(), Some(vec2(2.0, 0.0)));
+	}
+
+	#[test]
+	fn looping_clip_wraps_and_play_keeps_its_place() {
+		let mut animation = animation();
+		animation.play("reel");
+		animation.advance(0.3);
+		assert_eq!(animation.current_frame(), Some(vec2(5.0, 1.0)));
+		animation.play("reel");
+		animation.advance(0.25);
+		assert_eq!(animation.current_frame(), Some(vec2(4.0, 1.0)));
+		assert!(!animation.just_finished("reel"));
+
+		animation.restart("cast");
+		assert_eq!(animation.current_frame(), Some(vec2(0.0, 0.0)));
+	}
+
+	const DT: f32 = 1.0 / 60.0;
+
+	#[test]
+	fn jump_allowed_just_after_leaving_a_ledge_or_just_before_landing() {
+		let mut controller = Controller::default();
+		controller.jump(DT, true, false, 0.0);
+		//Walked off the ledge a few steps ago
+		for _ in 0..3 {
+			assert_eq!(controller.jump(DT, false, false, 10.0), 10.0);
+		}
+		assert_eq!(controller.jump(DT, false, true, 10.0), -controller.jump_speed);
+
+		let mut controller = Controller::default();
+		for _ in 0..20 {
+			controller.jump(DT, false, false, 10.0);
+		}
+		assert_eq!(controller.jump(DT, false, true, 10.0), 10.0);
+		//Still holding jump when landing a few steps later
+		assert_eq!(controller.jump(DT, false, true, 10.0), 10.0);
+		assert_eq!(controller.jump(DT, true, true, 0.0), -controller.jump_speed);
+		//Holding it after that doesn't jump again
+		assert_eq!(controller.jump(DT, true, true, 0.0), 0.0);
+	}
+
+	#[test]
+	fn letting_go_of_jump_cuts_it_short() {
+		let mut controller = Controller::default();
+		let vel_y = controller.jump(DT, true, true, 0.0);
+		assert_eq!(controller.jump(DT, false, true, vel_y), vel_y);
+		assert_eq!(controller.jump(DT, false, false, vel_y), vel_y * controller.release_cut);
+		//Only once per jump
+		assert_eq!(controller.jump(DT, false, false, vel_y), vel_y);
+	}
+}
@@ -0,0 +1,357 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
+pub mod input {
+	use std::collections::{BTreeMap, BTreeSet, HashSet};
+	use macroquad::prelude::*;
+	use serde::{Deserialize, Serialize};
+
+	/**
+	 * Things the player can ask for, behaviours read these instead of particular keys or buttons
+	 */
+	#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+	pub enum Action {
+		MoveLeft,
+		MoveRight,
+		Down,
+		Jump,
+		Slide,
+		Respawn,
+		ZoomIn,
+		ZoomOut,
+	}
+
+	impl Action {
+		pub const ALL: [Action; 8] = [
+			Action::MoveLeft, Action::MoveRight, Action::Down, Action::Jump, Action::Slide,
+			Action::Respawn, Action::ZoomIn, Action::ZoomOut,
+		];
+	}
+
+	/**
+	 * Keys that can be named in the input config
+	 */
+	const KEY_NAMES: [(&str, KeyCode); 52] = [
+		("A", KeyCode::A), ("B", KeyCode::B), ("C", KeyCode::C), ("D", KeyCode::D), ("E", KeyCode::E),
+		("F", KeyCode::F), ("G", KeyCode::G), ("H", KeyCode::H), ("I", KeyCode::I), ("J", KeyCode::J),
+		("K", KeyCode::K), ("L", KeyCode::L), ("M", KeyCode::M), ("N", KeyCode::N), ("O", KeyCode::O),
+		("P", KeyCode::P), ("Q", KeyCode::Q), ("R", KeyCode::R), ("S", KeyCode::S), ("T", KeyCode::T),
+		("U", KeyCode::U), ("V", KeyCode::V), ("W", KeyCode::W), ("X", KeyCode::X), ("Y", KeyCode::Y),
+		("Z", KeyCode::Z),
+		("0", KeyCode::Key0), ("1", KeyCode::Key1), ("2", KeyCode::Key2), ("3", KeyCode::Key3), ("4", KeyCode::Key4),
+		("5", KeyCode::Key5), ("6", KeyCode::Key6), ("7", KeyCode::Key7), ("8", KeyCode::Key8), ("9", KeyCode::Key9),
+		("Space", KeyCode::Space), ("Enter", KeyCode::Enter), ("Tab", KeyCode::Tab), ("Backspace", KeyCode::Backspace),
+		("Left", KeyCode::Left), ("Right", KeyCode::Right), ("Up", KeyCode::Up), ("Down", KeyCode::Down),
+		("LeftShift", KeyCode::LeftShift), ("RightShift", KeyCode::RightShift),
+		("LeftControl", KeyCode::LeftControl), ("RightControl", KeyCode::RightControl),
+		("LeftAlt", KeyCode::LeftAlt), ("RightAlt", KeyCode::RightAlt),
+		("Minus", KeyCode::Minus), ("Equal", KeyCode::Equal),
+	];
+
+	/**
+	 * Keyboard key written by name in the input config, like "Space" or "A"
+	 */
+	#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+	#[serde(try_from = "String", into = "String")]
+	pub struct Key(pub KeyCode);
+
+	impl TryFrom<String> for Key {
+		type Error = String;
+
+		fn try_from(name: String) -> Result<Self, Self::Error> {
+			KEY_NAMES.iter()
+				.find(|(key_name, _)| *key_name == name)
+				.map(|(_, code)| Key(*code))
+				.ok_or_else(|| format!("Unknown key '{}'", name))
+		}
+	}
+
+	impl From<Key> for String {
+		fn from(key: Key) -> Self {
+			KEY_NAMES.iter()
+				.find(|(_, code)| *code == key.0)
+				.map_or_else(|| format!("{:?}", key.0), |(name, _)| name.to_string())
+		}
+	}
+
+	#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+	pub enum GamepadButton {
+		South,
+		East,
+		North,
+		West,
+		LeftTrigger,
+		RightTrigger,
+		Select,
+		Start,
+		DPadUp,
+		DPadDown,
+		DPadLeft,
+		DPadRight,
+	}
+
+	#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+	pub enum Binding {
+		Key(Key),
+		Button(GamepadButton),
+		WheelUp,
+		WheelDown,
+	}
+
+	/**
+	 * Which bindings trigger each action, loaded from a config file and changeable while the game runs
+	 */
+	#[derive(Clone, Serialize, Deserialize)]
+	pub struct InputConfig {
+		pub bindings: BTreeMap<Action, Vec<Binding>>,
+	}
+
+	impl Default for InputConfig {
+		fn default() -> Self {
+			let key = |name: &str| Binding::Key(Key::try_from(name.to_string()).unwrap());
+			Self {
+				bindings: BTreeMap::from([
+					(Action::MoveLeft, vec![key("A"), key("Left"), Binding::Button(GamepadButton::DPadLeft)]),
+					(Action::MoveRight, vec![key("D"), key("Right"), Binding::Button(GamepadButton::DPadRight)]),
+					(Action::Down, vec![key("S"), key("Down"), Binding::Button(GamepadButton::DPadDown)]),
+					(Action::Jump, vec![key("Space"), key("W"), key("Up"), Binding::Button(GamepadButton::South)]),
+					(Action::Slide, vec![key("S"), key("Down"), Binding::Button(GamepadButton::East)]),
+					(Action::Respawn, vec![key("R"), Binding::Button(GamepadButton::Select)]),
+					(Action::ZoomIn, vec![Binding::WheelUp, key("Equal"), Binding::Button(GamepadButton::RightTrigger)]),
+					(Action::ZoomOut, vec![Binding::WheelDown, key("Minus"), Binding::Button(GamepadButton::LeftTrigger)]),
+				]),
+			}
+		}
+	}
+
+	impl InputConfig {
+		pub fn parse(source: &str) -> Result<InputConfig, ron::error::SpannedError> {
+			ron::from_str(source)
+		}
+
+		/**
+		 * Falls back to the default bindings if the file is missing or broken
+		 */
+		pub async fn load(path: &str) -> InputConfig {
+			let parsed = match load_string(path).await {
+				Ok(source) => InputConfig::parse(&source).map_err(|e| e.to_string()),
+				Err(e) => Err(e.to_string())
+			};
+			parsed.unwrap_or_else(|e| {
+				println!("Could not load input config {}: {}", path, e);
+				InputConfig::default()
+			})
+		}
+
+		pub fn save(&self, path: &str) -> Result<(), String> {
+			let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+			std::fs::write(path, source).map_err(|e| e.to_string())
+		}
+
+		pub fn bindings(&self, action: Action) -> &[Binding] {
+			self.bindings.get(&action).map_or(&[], |bindings| bindings.as_slice())
+		}
+
+		/**
+		 * Make the key the only key for an action, taking it off any other action.
+		 * Gamepad and wheel bindings are kept
+		 */
+		pub fn rebind_key(&mut self, action: Action, key: KeyCode) {
+			for bindings in self.bindings.values_mut() {
+				bindings.retain(|binding| *binding != Binding::Key(Key(key)));
+			}
+			let bindings = self.bindings.entry(action).or_default();
+			bindings.retain(|binding| !matches!(binding, Binding::Key(_)));
+			bindings.insert(0, Binding::Key(Key(key)));
+		}
+	}
+
+	/**
+	 * Connected gamepads, only read when built with the gamepad feature
+	 */
+	pub struct Gamepads {
+		#[cfg(feature = "gamepad")]
+		gilrs: Option<gilrs::Gilrs>,
+		held: HashSet<GamepadButton>,	//Buttons down on the previous capture
+	}
+
+	impl Gamepads {
+		pub fn new() -> Self {
+			Self {
+				#[cfg(feature = "gamepad")]
+				gilrs: gilrs::Gilrs::new().map_err(|e| println!("Could not open gamepads: {}", e)).ok(),
+				held: HashSet::new(),
+			}
+		}
+
+		#[cfg(feature = "gamepad")]
+		fn buttons_down(&mut self) -> HashSet<GamepadButton> {
+			let mut down = HashSet::new();
+			if let Some(gilrs) = &mut self.gilrs {
+				//Button state only updates as events are drained
+				while gilrs.next_event().is_some() {}
+				for (_, gamepad) in gilrs.gamepads() {
+					for button in GamepadButton::ALL {
+						if gamepad.is_pressed(button.to_gilrs()) {
+							down.insert(button);
+						}
+					}
+				}
+			}
+			down
+		}
+
+		#[cfg(not(feature = "gamepad"))]
+		fn buttons_down(&mut self) -> HashSet<GamepadButton> {
+			HashSet::new()
+		}
+
+		/**
+		 * Buttons held now, and the ones among them that weren't held on the last poll
+		 */
+		fn poll(&mut self) -> (HashSet<GamepadButton>, HashSet<GamepadButton>) {
+			let down = self.buttons_down();
+			let pressed = down.difference(&self.held).copied().collect();
+			self.held = down.clone();
+			(down, pressed)
+		}
+	}
+
+	#[cfg(feature = "gamepad")]
+	impl GamepadButton {
+		const ALL: [GamepadButton; 12] = [
+			GamepadButton::South, GamepadButton::East, GamepadButton::North, GamepadButton::West,
+			GamepadButton::LeftTrigger, GamepadButton::RightTrigger, GamepadButton::Select, GamepadButton::Start,
+			GamepadButton::DPadUp, GamepadButton::DPadDown, GamepadButton::DPadLeft, GamepadButton::DPadRight,
+		];
+
+		fn to_gilrs(self) -> gilrs::Button {
+			match self {
+				GamepadButton::South => gilrs::Button::South,
+				GamepadButton::East => gilrs::Button::East,
+				GamepadButton::North => gilrs::Button::North,
+				GamepadButton::West => gilrs::Button::West,
+				GamepadButton::LeftTrigger => gilrs::Button::LeftTrigger,
+				GamepadButton::RightTrigger => gilrs::Button::RightTrigger,
+				GamepadButton::Select => gilrs::Button::Select,
+				GamepadButton::Start => gilrs::Button::Start,
+				GamepadButton::DPadUp => gilrs::Button::DPadUp,
+				GamepadButton::DPadDown => gilrs::Button::DPadDown,
+				GamepadButton::DPadLeft => gilrs::Button::DPadLeft,
+				GamepadButton::DPadRight => gilrs::Button::DPadRight,
+			}
+		}
+	}
+
+	/**
+	 * Snapshot of the player's input for a single frame, as actions.
+	 * Behaviours read from this instead of macroquad so the world can be stepped without a window
+	 */
+	#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+	pub struct InputState {
+		actions_down: BTreeSet<Action>,
+		zoom: f32,	//Zoom steps in, negative for out
+	}
+
+	impl InputState {
+		/**
+		 * Read the current frame's input from the window and gamepads through the configured bindings
+		 */
+		pub fn capture(config: &InputConfig, gamepads: &mut Gamepads) -> Self {
+			let (_, wheel_y) = mouse_wheel();
+			let (buttons_down, buttons_pressed) = gamepads.poll();
+			let is_down = |binding: &Binding| match binding {
+				Binding::Key(key) => is_key_down(key.0),
+				Binding::Button(button) => buttons_down.contains(button),
+				Binding::WheelUp => wheel_y > 0.0,
+				Binding::WheelDown => wheel_y < 0.0,
+			};
+			//Zooming happens once per press or wheel notch rather than for as long as it's held
+			let zoom_steps = |action: Action| config.bindings(action).iter().filter(|binding| match binding {
+				Binding::Key(key) => is_key_pressed(key.0),
+				Binding::Button(button) => buttons_pressed.contains(button),
+				Binding::WheelUp => wheel_y > 0.0,
+				Binding::WheelDown => wheel_y < 0.0,
+			}).count() as f32;
+			Self::resolve(config, is_down, zoom_steps(Action::ZoomIn) - zoom_steps(Action::ZoomOut))
+		}
+
+		/**
+		 * Every action with at least one of its bindings held
+		 */
+		fn resolve(config: &InputConfig, is_down: impl Fn(&Binding) -> bool, zoom: f32) -> Self {
+			Self {
+				actions_down: Action::ALL.into_iter()
+					.filter(|action| config.bindings(*action).iter().any(&is_down))
+					.collect(),
+				zoom,
+			}
+		}
+
+		#[cfg(test)]
+		pub fn with_actions(actions: &[Action]) -> Self {
+			Self {
+				actions_down: actions.iter().copied().collect(),
+				..Default::default()
+			}
+		}
+
+		/**
+		 * What capture would read with just these keys held
+		 */
+		#[cfg(test)]
+		pub fn with_keys(config: &InputConfig, keys: &[KeyCode]) -> Self {
+			Self::resolve(config, |binding| matches!(binding, Binding::Key(key) if keys.contains(&key.0)), 0.0)
+		}
+
+		/**
+		 * Fold a newly captured frame into input that hasn't been stepped yet.
+		 * Held actions follow the latest frame while zoom adds up until a step consumes it
+		 */
+		pub fn accumulate(&mut self, frame: InputState) {
+			self.actions_down = frame.actions_down;
+			self.zoom += frame.zoom;
+		}
+
+		/**
+		 * Called after a step has seen the input, so one-off events aren't applied twice
+		 */
+		pub fn clear_events(&mut self) {
+			self.zoom = 0.0;
+		}
+
+		pub fn is_down(&self, action: Action) -> bool {
+			self.actions_down.contains(&action)
+		}
+
+		pub fn zoom(&self) -> f32 {
+			self.zoom
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::input::{Action, Binding, InputConfig, Key};
+	use macroquad::prelude::KeyCode;
+
+	#[test]
+	fn config_round_trips_through_ron() {
+		let source = ron::ser::to_string(&InputConfig::default()).unwrap();
+		let config = InputConfig::parse(&source).unwrap();
+		assert!(config.bindings(Action::Jump).contains(&Binding::Key(Key(KeyCode::Space))));
+		assert!(InputConfig::parse(r#"(bindings: { Jump: [Key("NotAKey")] })"#).is_err());
+
+		let shipped = InputConfig::parse(&std::fs::read_to_string("res/input.ron").unwrap()).unwrap();
+		assert!(shipped.bindings(Action::MoveLeft).contains(&Binding::Key(Key(KeyCode::A))));
+	}
+
+	#[test]
+	fn rebinding_a_key_moves_it_between_actions() {
+		let mut config = InputConfig::default();
+		config.rebind_key(Action::Jump, KeyCode::D);
+
+		assert!(config.bindings(Action::Jump)[0] == Binding::Key(Key(KeyCode::D)));
+		assert!(!config.bindings(Action::Jump).contains(&Binding::Key(Key(KeyCode::Space))));
+		assert!(config.bindings(Action::Jump).iter().any(|binding| matches!(binding, Binding::Button(_))));
+		assert!(!config.bindings(Action::MoveRight).contains(&Binding::Key(Key(KeyCode::D))));
+	}
+}
@@ -0,0 +1,274 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
+pub mod level {
+	use crate::article::article::{Article, OutOfBounds, RenderLayer};
+	use crate::background::background::Layer;
+	use crate::behaviour::behaviour::Behaviour;
+	use crate::component::component::Components;
+	use crate::material::material::Material;
+	use crate::shape::shape::Polygon;
+	use crate::sheet::sheet::SpriteSheet;
+	use std::{collections::HashMap, rc::Rc};
+	use macroquad::prelude::*;
+	use serde::Deserialize;
+
+	pub type RectDef = (f32, f32, f32, f32);
+
+	/**
+	 * Description of a single article as written in a level file.
+	 * Any optional field left out keeps the default from Article::new
+	 */
+	#[derive(Deserialize)]
+	pub struct ArticleDef {
+		pub name: String,
+		#[serde(default)]
+		pub texture: Option<String>,
+		#[serde(default)]
+		pub sheet: Option<String>,	//Spritesheet metadata file describing the texture's frames
+		pub src: RectDef,
+		pub dest: RectDef,
+		#[serde(default)]
+		pub bounds: Option<Vec<RectDef>>,
+		#[serde(default)]
+		pub one_way: Vec<usize>,	//Indices of bounds that can be jumped through from below
+		#[serde(default)]
+		pub materials: Vec<Material>,	//One per bound, in the same order
+		#[serde(default)]
+		pub polygons: Option<Vec<Vec<(f32, f32)>>>,	//Convex, points clockwise, for slopes and ramps
+		#[serde(default)]
+		pub polygon_materials: Vec<Material>,	//One per polygon, in the same order
+		#[serde(default)]
+		pub mass: Option<f32>,
+		#[serde(default)]
+		pub elasticity: Option<f32>,
+		#[serde(default)]
+		pub friction: Option<f32>,
+		#[serde(default)]
+		pub cog: Option<(f32, f32)>,
+		#[serde(default)]
+		pub vel: Option<(f32, f32)>,
+		#[serde(default)]
+		pub direction: Option<(f32, f32)>,	//Passed to set_direction once the article is built
+		#[serde(default)]
+		pub behaviour: Option<String>,	//Id of a behaviour in the registry
+		#[serde(default)]
+		pub components: Components,
+		#[serde(default)]
+		pub reset_on_respawn: bool,
+		#[serde(default)]
+		pub trigger: bool,
+		#[serde(default)]
+		pub out_of_bounds: OutOfBounds,
+		#[serde(default)]
+		pub layer: RenderLayer,
+		#[serde(default)]
+		pub z: i32,
+	}
+
+	#[derive(Deserialize)]
+	pub struct Level {
+		pub name: String,
+		#[serde(default)]
+		pub bounds: Option<RectDef>,	//Area articles have to stay inside, unbounded if left out
+		#[serde(default)]
+		pub background: Vec<Layer>,	//Drawn in order behind every article, farthest first
+		pub articles: Vec<ArticleDef>,
+	}
+
+	/**
+	 * Ordered list of level files played one after another
+	 */
+	#[derive(Deserialize)]
+	pub struct LevelSequence {
+		pub levels: Vec<String>,
+		#[serde(skip)]
+		pub current: usize,
+	}
+
+	impl LevelSequence {
+		pub async fn load(path: &str) -> LevelSequence {
+			let parsed = match load_string(path).await {
+				Ok(source) => ron::from_str::<LevelSequence>(&source).map_err(|e| e.to_string()),
+				Err(e) => Err(e.to_string())
+			};
+			parsed.unwrap_or_else(|e| {
+				println!("Could not load level sequence {}: {}", path, e);
+				LevelSequence { levels: Vec::new(), current: 0 }
+			})
+		}
+
+		pub fn current_path(&self) -> Option<&str> {
+			self.levels.get(self.current).map(|path| path.as_str())
+		}
+
+		pub fn has_next(&self) -> bool {
+			self.current + 1 < self.levels.len()
+		}
+
+		/**
+		 * Move on to the next level, returning its path if there is one
+		 */
+		pub fn advance(&mut self) -> Option<&str> {
+			if self.has_next() {
+				self.current += 1;
+				return self.current_path();
+			}
+			None
+		}
+
+		pub fn restart(&mut self) {
+			self.current = 0;
+		}
+
+		/**
+		 * Jump to a level in the sequence, false if it isn't part of it
+		 */
+		pub fn select(&mut self, path: &str) -> bool {
+			match self.levels.iter().position(|level| level == path) {
+				Some(index) => {
+					self.current = index;
+					true
+				},
+				None => false
+			}
+		}
+	}
+
+	pub fn to_rect(r: &RectDef) -> Rect {
+		Rect::new(r.0, r.1, r.2, r.3)
+	}
+
+	pub fn from_rect(r: &Rect) -> RectDef {
+		(r.x, r.y, r.w, r.h)
+	}
+
+	impl ArticleDef {
+		pub fn build(&self, textures: &HashMap<String, Texture2D>, sheets: &HashMap<String, Rc<SpriteSheet>>, behaviours: &HashMap<String, Behaviour>) -> Article {
+			let mut article = Article::new(
+				to_rect(&self.src),
+				to_rect(&self.dest),
+				self.bounds.as_ref().map(|bounds| bounds.iter().map(to_rect).collect())
+			);
+			article.name = self.name.clone();
+			article.one_way = self.one_way.clone();
+			article.materials = self.materials.clone();
+			article.polygon_materials = self.polygon_materials.clone();
+			article.polygons = self.polygons.as_ref().map(|polygons| polygons.iter()
+				.map(|points| Polygon::new(points.iter().map(|(x, y)| vec2(*x, *y)).collect()))
+				.collect());
+			if let Some(path) = &self.texture {
+				article.texture = textures.get(path).cloned();
+			}
+			if let Some(path) = &self.sheet {
+				article.sheet = sheets.get(path).cloned();
+			}
+			if let Some(mass) = self.mass {
+				article.mass = mass;
+			}
+			if let Some(elasticity) = self.elasticity {
+				article.elasticity = elasticity;
+			}
+			if let Some(friction) = self.friction {
+				article.friction_coefficient = friction;
+			}
+			if let Some((x, y)) = self.cog {
+				article.cog = vec2(x, y);
+			}
+			if let Some((x, y)) = self.vel {
+				article.vel = vec2(x, y);
+			}
+			if let Some((x, y)) = self.direction {
+				article.set_direction(vec2(x, y));
+			}
+			if let Some(id) = &self.behaviour {
+				match behaviours.get(id) {
+					Some(behaviour) => behaviour.bind(&mut article),
+					None => println!("Unknown behaviour '{}' on article {}", id, self.name)
+				}
+			}
+			article.components = self.components.clone();
+			article.reset_on_respawn = self.reset_on_respawn;
+			article.trigger = self.trigger;
+			article.out_of_bounds = self.out_of_bounds;
+			article.layer = self.layer;
+			article.z = self.z;
+			article
+		}
+	}
+
+	impl Level {
+		pub fn parse(source: &str) -> Result<Level, ron::error::SpannedError> {
+			ron::from_str(source)
+		}
+
+		pub async fn load(path: &str) -> Option<Level> {
+			let source = match load_string(path).await {
+				Ok(s) => s,
+				Err(e) => {
+					println!("Could not load level: {}", e);
+					return None;
+				}
+			};
+			match Level::parse(&source) {
+				Ok(level) => Some(level),
+				Err(e) => {
+					println!("Could not parse level {}: {}", path, e);
+					None
+				}
+			}
+		}
+
+		/**
+		 * Load every texture referenced by the level once, keyed by path
+		 */
+		pub async fn load_textures(&self) -> HashMap<String, Texture2D> {
+			let mut textures = HashMap::<String, Texture2D>::new();
+			let layers = self.background.iter().map(|layer| &layer.texture);
+			for path in self.articles.iter().filter_map(|def| def.texture.as_ref()).chain(layers) {
+				if !textures.contains_key(path) {
+					match load_texture(path).await {
+						Ok(t) => { textures.insert(path.clone(), t); },
+						Err(e) => println!("Could not load texture: {}", e)
+					}
+				}
+			}
+			textures
+		}
+
+		/**
+		 * Load every spritesheet metadata file referenced by the level once, keyed by path
+		 */
+		pub async fn load_sheets(&self) -> HashMap<String, Rc<SpriteSheet>> {
+			let mut sheets = HashMap::<String, Rc<SpriteSheet>>::new();
+			for path in self.articles.iter().filter_map(|def| def.sheet.as_ref()) {
+				if !sheets.contains_key(path) {
+					if let Some(sheet) = SpriteSheet::load(path).await {
+						sheets.insert(path.clone(), Rc::new(sheet));
+					}
+				}
+			}
+			sheets
+		}
+
+		/**
+		 * Same as load_sheets but read straight from disk, for running without a window
+		 */
+		pub fn read_sheets(&self) -> HashMap<String, Rc<SpriteSheet>> {
+			let mut sheets = HashMap::<String, Rc<SpriteSheet>>::new();
+			for path in self.articles.iter().filter_map(|def| def.sheet.as_ref()) {
+				if !sheets.contains_key(path) {
+					match SpriteSheet::read(path) {
+						Ok(sheet) => { sheets.insert(path.clone(), Rc::new(sheet)); },
+						Err(e) => println!("Could not read spritesheet {}: {}", path, e)
+					}
+				}
+			}
+			sheets
+		}
+
+		pub fn build_articles(&self, textures: &HashMap<String, Texture2D>, sheets: &HashMap<String, Rc<SpriteSheet>>, behaviours: &HashMap<String, Behaviour>) -> HashMap<String, Article> {
+			self.articles.iter()
+				.map(|def| (def.name.clone(), def.build(textures, sheets, behaviours)))
+				.collect()
+		}
+	}
+}
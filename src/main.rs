@@ -5,35 +5,54 @@ use std::collections::HashMap;
 use macroquad::prelude::*;
 
 mod article;
+mod background;
+mod behaviour;
+mod broadphase;
+mod component;
+mod input;
+mod level;
+mod material;
+mod replay;
+mod save;
+mod shape;
+mod sheet;
 mod world;
-use article::article::Article;
+use article::article::{Article, Event};
+use crate::input::input::{Action, Binding, Gamepads, InputConfig, InputState};
+use crate::level::level::LevelSequence;
+use crate::replay::replay::{verify, Recording};
+use crate::save::save::SaveGame;
 use crate::world::world::*;
 
 
+const GAME_TITLE: &str = "Game";
+
 fn window_conf() -> Conf {
 	Conf {
-		window_title: "Game".to_owned(),
+		window_title: GAME_TITLE.to_owned(),
 		fullscreen: false,
 		..Default::default()
 	}
 }
 
 
-fn get_camera_track(camera_track: Vec2, camera: &mut Article) -> Vec2 {
-	
-	if is_key_down(KeyCode::F) {
-		print!("a");
-	}
+/**
+ * Longest frame the simulation will try to catch up on, so a stall doesn't turn into a burst of steps
+ */
+const MAX_FRAME_TIME: f32 = 0.25;
+const CAMERA_RECENTER: f32 = 0.3;	//Fraction of the camera track recovered per second
+
+fn get_camera_track(camera_track: Vec2, camera: &mut Article, frame_time: f32) -> Vec2 {
 	let camera_track_bounds = vec2(200.0, 100.0);
 
 	let normalized_bounds = Vec2::select(camera_track.cmpgt(Vec2::ZERO), camera_track_bounds, -camera_track_bounds);
 	//If camera track is out of bounds, then bring it back towards center
-	let mut camera_track = Vec2::select((camera_track+camera.vel).abs().cmpgt(camera_track_bounds), 
+	let moved = camera.vel * frame_time;
+	let mut camera_track = Vec2::select((camera_track+moved).abs().cmpgt(camera_track_bounds), 
 		normalized_bounds, 
-		camera_track + camera.vel
+		camera_track + moved
 	);
-	camera_track.x *= 0.995;
-	camera_track.y *= 0.995;
+	camera_track -= camera_track * CAMERA_RECENTER * frame_time;
 	if camera_track.abs().x <= 0.1 {
 		camera_track.x = 0.0;
 	}
@@ -52,29 +71,201 @@ async fn load_ui_textures() -> HashMap<String, Texture2D> {
 			}
 		}
 	}
-	return ui_textures;
+	ui_textures
+}
+
+
+/**
+ * Where the game is in moving from one level to the next
+ */
+enum Transition {
+	None,
+	FadeOut(f32),	//Seconds since the player reached the exit
+	Complete,	//Level complete screen, waiting for the player to continue
+	FadeIn(f32),	//Seconds since the next level was loaded
 }
 
+const FADE_SECONDS: f32 = 1.0;
+const INPUT_CONFIG_PATH: &str = "res/input.ron";
+const SAVE_PATH: &str = "save.ron";
+//Picks the action to rebind in the pause menu, one per action so adding an action won't compile until it has a key here
+const REBIND_KEYS: [KeyCode; Action::ALL.len()] = [
+	KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8,
+];
 
-fn global_forces(article: &mut Article) {
-	if article.mass.is_finite() {
-		//Gravity
-		article.vel.y += 0.4;
-		//Atmospheric Drag - Air Resistance
-		article.vel.x -= article.vel.x * 0.005;
-		article.vel.y -= article.vel.y * 0.005;
+/**
+ * Top level state of the game, level transitions happen within Playing
+ */
+#[derive(Clone, Copy, PartialEq)]
+enum GameState {
+	Title,
+	Playing,
+	Paused,	//World is drawn but not stepped
+	GameOver,	//Player ran out of health, waiting to respawn
+	ReplayFinished,	//Recorded input ran out or the world stopped matching the recording
+}
+
+/**
+ * What was asked for on the command line
+ */
+#[derive(Default)]
+struct Options {
+	record: Option<String>,	//Write input from the first attempt at a level to this file
+	replay: Option<String>,	//Play this recording back instead of reading input
+	verify: Option<String>,	//Replay this recording without a window and exit
+}
+
+impl Options {
+	fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+		let mut options = Options::default();
+		while let Some(arg) = args.next() {
+			let target = match arg.as_str() {
+				"--record" => &mut options.record,
+				"--replay" => &mut options.replay,
+				"--verify" => &mut options.verify,
+				_ => return Err(format!("Unknown argument {}", arg))
+			};
+			*target = Some(args.next().ok_or(format!("{} needs a file", arg))?);
+		}
+		Ok(options)
 	}
 }
 
-#[macroquad::main(window_conf)]
-async fn main() {
+/**
+ * Rebuild the saved level and put its articles back the way they were saved
+ */
+async fn load_save(sequence: &mut LevelSequence) -> Result<World, String> {
+	let save = SaveGame::read(SAVE_PATH)?;
+	if !sequence.select(&save.level) {
+		println!("Saved level {} is not in the level sequence", save.level);
+	}
+	let mut world = World::load(&save.level).await;
+	world.restore(&save);
+	Ok(world)
+}
+
+/**
+ * Finish a recording in progress and write it out
+ */
+fn save_recording(recording: &mut Option<Recording>, world: &World, path: Option<&String>) {
+	if let (Some(mut recording), Some(path)) = (recording.take(), path) {
+		recording.finish(world);
+		match recording.save(path) {
+			Ok(()) => println!("Saved recording to {}", path),
+			Err(e) => println!("Could not save recording: {}", e)
+		}
+	}
+}
+
+fn draw_fade(alpha: f32) {
+	set_default_camera();
+	draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, alpha.clamp(0.0, 1.0)));
+}
+
+/**
+ * A heading with a line of instructions underneath, centered on the screen
+ */
+fn draw_message(heading: &str, instructions: &str) {
+	for (i, line) in [heading, instructions].iter().enumerate() {
+		let size = if i == 0 { 64.0 } else { 32.0 };
+		let dims = measure_text(line, None, size as u16, 1.0);
+		draw_text(line, (screen_width() - dims.width) / 2.0, screen_height() / 2.0 + i as f32 * 60.0, size, WHITE);
+	}
+}
+
+fn draw_level_complete(level_name: &str, has_next: bool) {
+	set_default_camera();
+	clear_background(BLACK);
+	let heading = format!("{} complete", level_name);
+	if has_next {
+		draw_message(&heading, "Press Enter to continue");
+	} else {
+		draw_message(&heading, "All levels complete! Press Enter to play again");
+	}
+}
+
+fn draw_title() {
+	set_default_camera();
+	clear_background(BLACK);
+	draw_message(GAME_TITLE, "Press Enter to start, L to load, Escape to quit");
+}
+
+/**
+ * Every action with its bindings, numbered so one can be picked for rebinding
+ */
+fn draw_bindings(config: &InputConfig, rebinding: Option<Action>) {
+	for (i, action) in Action::ALL.iter().enumerate() {
+		let bindings: Vec<String> = config.bindings(*action).iter().map(|binding| match binding {
+			Binding::Key(key) => String::from(*key),
+			Binding::Button(button) => format!("{:?} button", button),
+			Binding::WheelUp => "Wheel up".to_string(),
+			Binding::WheelDown => "Wheel down".to_string(),
+		}).collect();
+		let line = if rebinding == Some(*action) {
+			format!("{}. {:?}: press a key", i + 1, action)
+		} else {
+			format!("{}. {:?}: {}", i + 1, action, bindings.join(", "))
+		};
+		draw_text(&line, 40.0, 60.0 + i as f32 * 30.0, 28.0, WHITE);
+	}
+}
+
+/**
+ * Drawn over the frozen world
+ */
+fn draw_overlay(heading: &str, instructions: &str) {
+	set_default_camera();
+	draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.6));
+	draw_message(heading, instructions);
+}
+
+fn main() {
+	let options = match Options::parse(std::env::args().skip(1)) {
+		Ok(options) => options,
+		Err(e) => {
+			println!("{}", e);
+			std::process::exit(2);
+		}
+	};
+	if let Some(path) = &options.verify {
+		match verify(path) {
+			Ok(hash) => println!("{} matches, final state {:016x}", path, hash),
+			Err(e) => {
+				println!("{} does not match: {}", path, e);
+				std::process::exit(1);
+			}
+		}
+		return;
+	}
+	macroquad::Window::from_config(window_conf(), game(options));
+}
+
+async fn game(options: Options) {
     //set_fullscreen(true);
     
+	let mut sequence = LevelSequence::load("res/levels/sequence.ron").await;
+	let mut world = World::load(sequence.current_path().unwrap_or_default()).await;
+	let mut transition = Transition::None;
+	let mut state = GameState::Title;
 
-	let mut articles: HashMap<String, Article> = load_articles().await;
-	let mut article_keys = Vec::<String>::new();
-	for (key, _) in &articles {
-		article_keys.push(key.clone());
+	let mut recording: Option<Recording> = None;
+	//Recorded input to play back, with how many steps have been taken and how it went
+	let mut replay: Option<(Recording, Vec<InputState>, usize)> = None;
+	let mut replay_result: Result<u64, String> = Ok(0);
+	if let Some(path) = &options.replay {
+		match Recording::read(path).and_then(|recorded| recorded.read_level().map(|_| recorded)) {
+			Ok(recorded) => {
+				world = World::load(&recorded.level).await;
+				let inputs = recorded.inputs();
+				replay = Some((recorded, inputs, 0));
+				state = GameState::Playing;
+				transition = Transition::FadeIn(0.0);
+			},
+			Err(e) => {
+				replay_result = Err(e);
+				state = GameState::ReplayFinished;
+			}
+		}
 	}
 
 	let camera_index = "Player".to_string();
@@ -84,49 +275,205 @@ async fn main() {
 	};
 
 	let ui_textures = load_ui_textures().await;
-	
+	let mut input_config = InputConfig::load(INPUT_CONFIG_PATH).await;
+	let mut gamepads = Gamepads::new();
+	let mut rebinding: Option<Action> = None;
+	let mut input = InputState::default();
+	let mut accumulator = 0.0;
 
 
-    while !is_key_down(KeyCode::Escape) {
+    loop {
         clear_background(WHITE);
-		
 
-		if let Some(player) = articles.get_mut(&camera_index) {
-			let zoom = *(player.scratchpad.get("zoom").unwrap_or(&0.0008));
+		if state == GameState::Title {
+			draw_title();
+			if is_key_pressed(KeyCode::Escape) {
+				break;
+			}
+			if is_key_pressed(KeyCode::Enter) {
+				state = GameState::Playing;
+				accumulator = 0.0;
+				transition = Transition::FadeIn(0.0);
+				if options.record.is_some() {
+					recording = Recording::start(sequence.current_path().unwrap_or_default())
+						.map_err(|e| println!("Could not start recording: {}", e))
+						.ok();
+				}
+			} else if is_key_pressed(KeyCode::L) {
+				match load_save(&mut sequence).await {
+					Ok(loaded) => {
+						world = loaded;
+						camera_track = Vec2::ZERO;
+						accumulator = 0.0;
+						state = GameState::Playing;
+						transition = Transition::FadeIn(0.0);
+					},
+					Err(e) => println!("Could not load {}: {}", SAVE_PATH, e)
+				}
+			}
+			next_frame().await;
+			continue;
+		}
 
-			camera_track = get_camera_track(camera_track, player);
-			camera.target = vec2(player.pos.x + player.cog.x - (camera_track.x) + 50.0, player.pos.y + player.cog.y + (camera_track.y));
-			camera.zoom = vec2(zoom, zoom * screen_width() / screen_height());
-			set_camera(&camera);
+		if state == GameState::ReplayFinished && is_key_pressed(KeyCode::Escape) {
+			break;
+		}
+
+		if state == GameState::GameOver && is_key_pressed(KeyCode::Enter) {
+			//Back to the last checkpoint, or the start of the level if none was reached
+			world.respawn(true);
+			camera_track = Vec2::ZERO;
+			accumulator = 0.0;
+			state = GameState::Playing;
+			transition = Transition::FadeIn(0.0);
+		}
+		if state == GameState::Paused {
+			if let Some(action) = rebinding {
+				//The next key pressed replaces the action's keys, Escape leaves them as they were
+				if let Some(key) = get_last_key_pressed() {
+					if key != KeyCode::Escape {
+						input_config.rebind_key(action, key);
+						if let Err(e) = input_config.save(INPUT_CONFIG_PATH) {
+							println!("Could not save input config: {}", e);
+						}
+					}
+					rebinding = None;
+				}
+			} else if let Some((_, action)) = REBIND_KEYS.iter().zip(Action::ALL).find(|(key, _)| is_key_pressed(**key)) {
+				rebinding = Some(action);
+			} else if is_key_pressed(KeyCode::Escape) {
+				state = GameState::Playing;
+			}
+		} else if state == GameState::Playing && is_key_pressed(KeyCode::Escape) && !matches!(transition, Transition::Complete) {
+			state = GameState::Paused;
+		}
+		if state == GameState::Paused && rebinding.is_none() && is_key_pressed(KeyCode::Q) {
+			if replay.is_some() {
+				break;
+			}
+			save_recording(&mut recording, &world, options.record.as_ref());
+			state = GameState::Title;
+			sequence.restart();
+			world = World::load(sequence.current_path().unwrap_or_default()).await;
+			next_frame().await;
+			continue;
+		}
+		if state == GameState::Paused && rebinding.is_none() && replay.is_none() {
+			if is_key_pressed(KeyCode::S) {
+				match world.save_game(sequence.current_path().unwrap_or_default()).save(SAVE_PATH) {
+					Ok(()) => println!("Saved to {}", SAVE_PATH),
+					Err(e) => println!("Could not save: {}", e)
+				}
+			} else if is_key_pressed(KeyCode::L) {
+				match load_save(&mut sequence).await {
+					Ok(loaded) => {
+						//The recording only covers play from the start of a level
+						save_recording(&mut recording, &world, options.record.as_ref());
+						world = loaded;
+						camera_track = Vec2::ZERO;
+						accumulator = 0.0;
+						state = GameState::Playing;
+						transition = Transition::FadeIn(0.0);
+					},
+					Err(e) => println!("Could not load {}: {}", SAVE_PATH, e)
+				}
+			}
+		}
+
+		if let Transition::Complete = transition {
+			draw_level_complete(&world.name, sequence.has_next());
+			if is_key_pressed(KeyCode::Enter) {
+				let next_path = match sequence.advance() {
+					Some(path) => path.to_string(),
+					None => {
+						//Finished the last level, start over with fresh health
+						world.articles.clear();
+						sequence.restart();
+						sequence.current_path().unwrap_or_default().to_string()
+					}
+				};
+				let mut next_world = World::load(&next_path).await;
+				next_world.carry_player_health(&world);
+				world = next_world;
+				camera_track = Vec2::ZERO;
+				accumulator = 0.0;
+				transition = Transition::FadeIn(0.0);
+			}
+			next_frame().await;
+			continue;
 		}
 		
 
-		for index in article_keys.iter() {
-			if let Some(mut article) = articles.remove(index) {
-				if !article.do_destroy {
-					if article.mass.is_finite() {
-						global_forces(&mut article);
+		//Run as many fixed steps as the time since the last frame allows, the remainder carries over
+		let frame_time = get_frame_time().min(MAX_FRAME_TIME);
+		//Freeze the world while fading out of a finished level or when not playing
+		if state == GameState::Playing && !matches!(transition, Transition::FadeOut(_)) {
+			input.accumulate(InputState::capture(&input_config, &mut gamepads));	//Input phase, gathered once a frame for the steps below
+			accumulator += frame_time;
+			while accumulator >= DT {
+				if let Some((recorded, inputs, steps_taken)) = &mut replay {
+					//Recorded input stands in for the player's
+					let Some(recorded_input) = inputs.get(*steps_taken) else {
+						state = GameState::ReplayFinished;
+						break;
+					};
+					world.step(recorded_input);
+					*steps_taken += 1;
+					replay_result = recorded.check(*steps_taken, &world).map(|_| world.state_hash());
+					if replay_result.is_err() {
+						state = GameState::ReplayFinished;
+						break;
 					}
-					article.tick(&mut articles);
-					if article.mass.is_finite() {
-						article.calculate_collisions(&mut articles);
+				} else {
+					world.step(&input);
+					if let Some(recording) = &mut recording {
+						recording.record(&input, &world);
 					}
+				}
+				input.clear_events();
+				accumulator -= DT;
+			}
+		}
+		let alpha = accumulator / DT;
 
-					
-					article.draw();
+		//Render phase, only once every step for this frame is done so nothing is drawn where it was part way through
+		if let Some(player) = world.articles.get_mut(&camera_index) {
+			let zoom = player.components.camera_zoom.as_ref().map_or(0.0008, |camera| camera.zoom);
+			let player_pos = player.render_pos(alpha);
 
-					articles.insert(index.clone(), article);
-				}
-				//If do destroy is set, article is dereferenced and freed
+			if state == GameState::Playing {
+				camera_track = get_camera_track(camera_track, player, frame_time);
+			}
+			camera.target = vec2(player_pos.x + player.cog.x - (camera_track.x) + 50.0, player_pos.y + player.cog.y + (camera_track.y));
+			camera.zoom = vec2(zoom, zoom * screen_width() / screen_height());
+			set_camera(&camera);
+		}
+
+		world.background.draw(&camera, get_time() as f32);
+		world.draw(alpha);
+
+		for event in world.take_events() {
+			if replay.is_some() {
+				continue;	//The replay runs until its input does
+			}
+			if event == Event::LevelExit && matches!(transition, Transition::None) {
+				transition = Transition::FadeOut(0.0);
+				save_recording(&mut recording, &world, options.record.as_ref());
+			}
+			if event == Event::PlayerDied {
+				state = GameState::GameOver;
+				save_recording(&mut recording, &world, options.record.as_ref());
 			}
 		}
 
 
 		//Paint UI Fixtures last
-		if let Some(player) = articles.get_mut(&camera_index) {
+		if let Some(player) = world.articles.get_mut(&camera_index) {
 
-			let player_health = *(player.scratchpad.get("health").unwrap_or(&5.0)) as i32;
-			let avail_player_health = *(player.scratchpad.get("avail_health").unwrap_or(&5.0)) as i32;
+			let (player_health, avail_player_health) = match &player.components.health {
+				Some(health) => (health.current as i32, health.max as i32),
+				None => (5, 5)
+			};
 
 			let ui_scale = 0.00200;
 			camera.zoom = vec2(ui_scale, ui_scale*1.2);
@@ -146,7 +493,32 @@ async fn main() {
 				}
 			}
 		}
+
+		transition = match transition {
+			Transition::FadeOut(t) => {
+				draw_fade(t / FADE_SECONDS);
+				if t >= FADE_SECONDS { Transition::Complete } else { Transition::FadeOut(t + get_frame_time()) }
+			},
+			Transition::FadeIn(t) => {
+				draw_fade(1.0 - t / FADE_SECONDS);
+				if t >= FADE_SECONDS { Transition::None } else { Transition::FadeIn(t + get_frame_time()) }
+			},
+			other => other
+		};
+
+		match state {
+			GameState::Paused => {
+				draw_overlay("Paused", "Escape to resume, Q to quit to the title, S to save, L to load, or a number to rebind");
+				draw_bindings(&input_config, rebinding);
+			},
+			GameState::GameOver => draw_overlay("Game over", "Press Enter to try again"),
+			GameState::ReplayFinished => match &replay_result {
+				Ok(hash) => draw_overlay(&format!("Replay matched, final state {:016x}", hash), "Press Escape to quit"),
+				Err(e) => draw_overlay(&format!("Replay diverged: {}", e), "Press Escape to quit"),
+			},
+			_ => ()
+		}
 		
         next_frame().await
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,86 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
+pub mod material {
+	use serde::{Deserialize, Serialize};
+
+	/**
+	 * What a bound is made of. Two bounds touching get their friction and bounce from the pair
+	 */
+	#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+	pub enum Material {
+		Snow,
+		Ice,
+		Grass,
+		Brick,
+		Water,
+	}
+
+	impl Material {
+		/**
+		 * How much of an article's usual slowing down applies on this material, 1 is full traction
+		 */
+		pub fn grip(&self) -> f32 {
+			match self {
+				Material::Snow => 0.8,
+				Material::Ice => 0.05,
+				Material::Grass => 1.0,
+				Material::Brick => 0.9,
+				Material::Water => 0.5,
+			}
+		}
+
+		/**
+		 * Fraction of speed into the surface given back as a bounce
+		 */
+		pub fn restitution(&self) -> f32 {
+			match self {
+				Material::Snow => 0.0,
+				Material::Ice => 0.05,
+				Material::Grass => 0.0,
+				Material::Brick => 0.1,
+				Material::Water => 0.0,
+			}
+		}
+	}
+
+	/**
+	 * How two touching bounds behave, a bound without a material doesn't change anything
+	 */
+	#[derive(Clone, Copy, Debug, PartialEq)]
+	pub struct Contact {
+		pub grip: f32,
+		pub restitution: f32,
+	}
+
+	impl Contact {
+		pub fn between(a: Option<Material>, b: Option<Material>) -> Contact {
+			let grip = |material: Option<Material>| material.map_or(1.0, |material| material.grip());
+			let restitution = |material: Option<Material>| material.map_or(0.0, |material| material.restitution());
+			Contact {
+				grip: grip(a) * grip(b),	//Either side being slippery makes the contact slippery
+				restitution: restitution(a).max(restitution(b)),	//The bouncier side wins
+			}
+		}
+
+		/**
+		 * Velocity kept per step of contact for an article keeping the given fraction of its speed over that step
+		 */
+		pub fn friction(&self, step_friction: f32) -> f32 {
+			1.0 - (1.0 - step_friction) * self.grip
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::material::{Contact, Material};
+
+	#[test]
+	fn ice_keeps_things_sliding_and_grass_holds_them() {
+		let walking = 0.85;
+		assert_eq!(Contact::between(None, None).friction(walking), walking);
+		assert_eq!(Contact::between(None, Some(Material::Grass)).friction(walking), walking);
+		assert!(Contact::between(None, Some(Material::Ice)).friction(walking) > 0.99);
+		assert!(Contact::between(Some(Material::Grass), Some(Material::Ice)).friction(walking) > 0.99);
+		assert_eq!(Contact::between(Some(Material::Brick), Some(Material::Ice)).restitution, Material::Brick.restitution());
+	}
+}
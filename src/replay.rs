@@ -0,0 +1,246 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
+pub mod replay {
+	use crate::input::input::InputState;
+	use crate::level::level::Level;
+	use crate::world::world::World;
+	use serde::{Deserialize, Serialize};
+	use std::collections::HashMap;
+
+	const HASH_INTERVAL: usize = 60;	//Steps between world state hashes, so a replay can say roughly when it went wrong
+
+	/**
+	 * Hash of a level file's contents. A recording only replays against the level it was made on
+	 */
+	pub fn level_seed(source: &str) -> u64 {
+		let mut hasher = StableHasher::default();
+		hasher.write(source.as_bytes());
+		hasher.finish()
+	}
+
+	/**
+	 * 64 bit FNV-1a over bytes fed in explicitly. Unlike the standard library's hasher its output is fixed,
+	 * so seeds and state hashes saved in a recording still match after a compiler upgrade or on another platform
+	 */
+	pub struct StableHasher(u64);
+
+	impl Default for StableHasher {
+		fn default() -> Self {
+			Self(0xcbf2_9ce4_8422_2325)
+		}
+	}
+
+	impl StableHasher {
+		pub fn write(&mut self, bytes: &[u8]) {
+			for byte in bytes {
+				self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+			}
+		}
+
+		/**
+		 * Text followed by a terminator, so neighbouring strings can't run into each other
+		 */
+		pub fn write_str(&mut self, text: &str) {
+			self.write(text.as_bytes());
+			self.write(&[0xff]);
+		}
+
+		pub fn write_f32(&mut self, value: f32) {
+			self.write(&value.to_bits().to_le_bytes());
+		}
+
+		pub fn finish(&self) -> u64 {
+			self.0
+		}
+	}
+
+	/**
+	 * Input for every step of one attempt at a level, with state hashes to check a replay against
+	 */
+	#[derive(Serialize, Deserialize)]
+	pub struct Recording {
+		pub level: String,	//Path of the level file
+		pub seed: u64,
+		steps: Vec<(u32, InputState)>,	//Runs of identical input stored once with the number of steps they last
+		hashes: Vec<u64>,	//World state every HASH_INTERVAL steps
+		final_hash: Option<u64>,
+		#[serde(skip)]
+		step_count: usize,
+	}
+
+	impl Recording {
+		/**
+		 * Start recording on a level, reading the level file to seed the recording
+		 */
+		pub fn start(level_path: &str) -> Result<Recording, String> {
+			let source = std::fs::read_to_string(level_path).map_err(|e| e.to_string())?;
+			Ok(Recording {
+				level: level_path.to_string(),
+				seed: level_seed(&source),
+				steps: Vec::new(),
+				hashes: Vec::new(),
+				final_hash: None,
+				step_count: 0,
+			})
+		}
+
+		/**
+		 * Called after each step with the input the step was given
+		 */
+		pub fn record(&mut self, input: &InputState, world: &World) {
+			match self.steps.last_mut() {
+				Some((count, last)) if last == input => *count += 1,
+				_ => self.steps.push((1, input.clone()))
+			}
+			self.step_count += 1;
+			if self.step_count.is_multiple_of(HASH_INTERVAL) {
+				self.hashes.push(world.state_hash());
+			}
+		}
+
+		pub fn finish(&mut self, world: &World) {
+			self.final_hash = Some(world.state_hash());
+		}
+
+		pub fn save(&self, path: &str) -> Result<(), String> {
+			let source = ron::ser::to_string(self).map_err(|e| e.to_string())?;
+			std::fs::write(path, source).map_err(|e| e.to_string())
+		}
+
+		pub fn read(path: &str) -> Result<Recording, String> {
+			let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+			let mut recording: Recording = ron::from_str(&source).map_err(|e| e.to_string())?;
+			recording.step_count = recording.steps.iter().map(|(count, _)| *count as usize).sum();
+			Ok(recording)
+		}
+
+		/**
+		 * The level the recording was made on, refused if the file has changed since
+		 */
+		pub fn read_level(&self) -> Result<Level, String> {
+			let source = std::fs::read_to_string(&self.level).map_err(|e| e.to_string())?;
+			if level_seed(&source) != self.seed {
+				return Err(format!("{} has changed since the recording was made", self.level));
+			}
+			Level::parse(&source).map_err(|e| e.to_string())
+		}
+
+		/**
+		 * Input for every step in order
+		 */
+		pub fn inputs(&self) -> Vec<InputState> {
+			self.steps.iter()
+				.flat_map(|(count, input)| std::iter::repeat_n(input.clone(), *count as usize))
+				.collect()
+		}
+
+		/**
+		 * Compare a replaying world with the recording once the given number of steps have been taken
+		 */
+		pub fn check(&self, steps_taken: usize, world: &World) -> Result<(), String> {
+			if steps_taken.is_multiple_of(HASH_INTERVAL) {
+				if let Some(expected) = self.hashes.get(steps_taken / HASH_INTERVAL - 1) {
+					if *expected != world.state_hash() {
+						return Err(format!("Diverged within the {} steps before step {}", HASH_INTERVAL, steps_taken));
+					}
+				}
+			}
+			if steps_taken == self.step_count {
+				match self.final_hash {
+					Some(expected) if expected != world.state_hash() => return Err("Final state differs".to_string()),
+					None => return Err("Recording was never finished".to_string()),
+					_ => ()
+				}
+			}
+			Ok(())
+		}
+
+		/**
+		 * Step the world through every recorded input, stopping at the first difference
+		 */
+		pub fn replay(&self, world: &mut World) -> Result<u64, String> {
+			for (i, input) in self.inputs().iter().enumerate() {
+				world.step(input);
+				self.check(i + 1, world)?;
+			}
+			Ok(world.state_hash())
+		}
+	}
+
+	/**
+	 * Replay a recording without a window, returning the final state hash if it matched all the way through
+	 */
+	pub fn verify(path: &str) -> Result<u64, String> {
+		let recording = Recording::read(path)?;
+		let level = recording.read_level()?;
+		let mut world = World::from_level(&level, &HashMap::new(), &level.read_sheets());
+		recording.replay(&mut world)
+	}
+}
+
+#[cfg(test)]
+#[allow(clippy::tabs_in_doc_comments)]
+mod tests {
+	use super::replay::{level_seed, verify, Recording};
+	use crate::input::input::{Action, InputState};
+	use crate::level::level::Level;
+	use crate::world::world::World;
+	use std::collections::HashMap;
+
+	const LEVEL: &str = "res/levels/level_1.ron";
+
+	fn level_world() -> World {
+		let level = Level::parse(&std::fs::read_to_string(LEVEL).unwrap()).unwrap();
+		World::from_level(&level, &HashMap::new(), &level.read_sheets())
+	}
+
+	/**
+	 * Walk right, jump a few times and slide, long enough for the fisherman to cast
+	 */
+	fn record(steps: usize) -> Recording {
+		let mut recording = Recording::start(LEVEL).unwrap();
+		let mut world = level_world();
+		for i in 0..steps {
+			let mut actions = vec![Action::MoveRight];
+			if i % 90 == 0 {
+				actions.push(Action::Jump);
+			}
+			if i > 400 {
+				actions = vec![Action::Slide];
+			}
+			let input = InputState::with_actions(&actions);
+			world.step(&input);
+			recording.record(&input, &world);
+		}
+		recording.finish(&world);
+		recording
+	}
+
+	#[test]
+	fn verify_reproduces_the_recorded_run() {
+		let path = std::env::temp_dir().join("replay_reproduces.ron");
+		let path = path.to_str().unwrap();
+		let recording = record(600);
+		recording.save(path).unwrap();
+
+		let loaded = Recording::read(path).unwrap();
+		assert_eq!(loaded.inputs().len(), 600);
+		assert!(verify(path).is_ok());
+		std::fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn replay_from_a_different_state_is_caught() {
+		let recording = record(200);
+		let mut world = level_world();
+		//One step ahead of where the recording started
+		world.step(&InputState::with_actions(&[Action::MoveLeft]));
+		assert!(recording.replay(&mut world).is_err());
+	}
+
+	#[test]
+	fn level_seed_is_plain_fnv_1a() {
+		//Published FNV-1a test vectors, recordings rely on these never changing
+		assert_eq!(level_seed(""), 0xcbf2_9ce4_8422_2325);
+		assert_eq!(level_seed("a"), 0xaf63_dc4c_8601_ec8c);
+	}
+}
@@ -0,0 +1,158 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
+pub mod save {
+	use crate::article::article::Article;
+	use crate::component::component::Components;
+	use crate::level::level::{from_rect, to_rect, RectDef};
+	use crate::material::material::Material;
+	use macroquad::math::Vec2;
+	use serde::{Deserialize, Serialize};
+	use std::collections::BTreeMap;
+
+	/**
+	 * Everything about an article the simulation can change.
+	 * Textures, spritesheets and behaviours aren't saved, the article is rebuilt from the level
+	 * file on load so they are bound by name just as when the level is first played
+	 */
+	#[derive(Serialize, Deserialize)]
+	pub struct ArticleState {
+		pub pos: Vec2,
+		pub vel: Vec2,
+		pub frame: Option<RectDef>,	//Source rect on the spritesheet
+		pub flip_x: bool,
+		pub flip_y: bool,
+		#[serde(default)]
+		pub rotation: f32,	//Set by the pose, so a sliding sprite still lies on its side
+		pub bounds: Option<Vec<RectDef>>,	//Mirrored along with the sprite, so they follow its flip
+		pub components: Components,
+		pub attached: Option<String>,
+		pub attached_to: Vec<String>,
+		#[serde(default)]
+		pub dropping_through: Option<String>,
+		#[serde(default)]
+		pub footing: Option<Material>,
+		#[serde(default)]
+		pub in_water: Option<String>,
+		#[serde(default)]
+		pub submerged: f32,
+	}
+
+	impl ArticleState {
+		pub fn capture(article: &Article) -> ArticleState {
+			ArticleState {
+				pos: article.pos,
+				vel: article.vel,
+				frame: article.params.source.as_ref().map(from_rect),
+				flip_x: article.params.flip_x,
+				flip_y: article.params.flip_y,
+				rotation: article.params.rotation,
+				bounds: article.bounds.as_ref().map(|bounds| bounds.iter().map(from_rect).collect()),
+				components: article.components.clone(),
+				attached: article.attached.clone(),
+				attached_to: article.attached_to.clone(),
+				dropping_through: article.dropping_through.clone(),
+				footing: article.footing,
+				in_water: article.in_water.clone(),
+				submerged: article.submerged,
+			}
+		}
+
+		pub fn apply(&self, article: &mut Article) {
+			article.pos = self.pos;
+			article.prev_pos = self.pos;
+			article.vel = self.vel;
+			article.params.source = self.frame.as_ref().map(to_rect);
+			article.params.flip_x = self.flip_x;
+			article.params.flip_y = self.flip_y;
+			article.params.rotation = self.rotation;
+			article.bounds = self.bounds.as_ref().map(|bounds| bounds.iter().map(to_rect).collect());
+			article.components = self.components.clone();
+			article.attached = self.attached.clone();
+			article.attached_to = self.attached_to.clone();
+			article.dropping_through = self.dropping_through.clone();
+			article.footing = self.footing;
+			article.in_water = self.in_water.clone();
+			article.submerged = self.submerged;
+		}
+	}
+
+	/**
+	 * A level part way through. Articles missing from the save had been destroyed
+	 */
+	#[derive(Serialize, Deserialize)]
+	pub struct SaveGame {
+		pub level: String,	//Path of the level file the articles are rebuilt from
+		pub spawn: Vec2,
+		pub player_dead: bool,
+		pub articles: BTreeMap<String, ArticleState>,
+	}
+
+	impl SaveGame {
+		pub fn save(&self, path: &str) -> Result<(), String> {
+			let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+			std::fs::write(path, source).map_err(|e| e.to_string())
+		}
+
+		pub fn read(path: &str) -> Result<SaveGame, String> {
+			let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+			ron::from_str(&source).map_err(|e| e.to_string())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::save::SaveGame;
+	use crate::input::input::{Action, InputState};
+	use crate::level::level::Level;
+	use crate::world::world::{World, TICK_RATE};
+	use std::collections::HashMap;
+
+	const LEVEL: &str = "res/levels/level_1.ron";
+
+	fn level_world() -> World {
+		let level = Level::parse(&std::fs::read_to_string(LEVEL).unwrap()).unwrap();
+		World::from_level(&level, &HashMap::new(), &level.read_sheets())
+	}
+
+	#[test]
+	fn restored_world_carries_on_the_same() {
+		let mut world = level_world();
+		let destroyed = "Enemy-1".to_string();
+		world.articles.get_mut(&destroyed).unwrap().do_destroy = true;
+		let input = InputState::with_actions(&[Action::MoveRight]);
+		for _ in 0..(3.0 * TICK_RATE) as usize {
+			world.step(&input);
+		}
+
+		let source = ron::to_string(&world.save_game(LEVEL)).unwrap();
+		let save: SaveGame = ron::from_str(&source).unwrap();
+		let mut restored = level_world();
+		restored.restore(&save);
+
+		assert!(!restored.articles.contains_key(&destroyed));
+		assert_eq!(restored.state_hash(), world.state_hash());
+		for _ in 0..(2.0 * TICK_RATE) as usize {
+			world.step(&input);
+			restored.step(&input);
+		}
+		assert_eq!(restored.state_hash(), world.state_hash());
+	}
+
+	#[test]
+	fn restored_slide_keeps_the_player_on_its_side() {
+		let mut world = level_world();
+		for _ in 0..TICK_RATE as usize {
+			world.step(&InputState::with_actions(&[Action::Slide]));
+		}
+		assert!(world.articles["Player"].is_pose("sliding"));
+
+		let source = ron::to_string(&world.save_game(LEVEL)).unwrap();
+		let mut restored = level_world();
+		restored.restore(&ron::from_str(&source).unwrap());
+		let player = &restored.articles["Player"];
+		assert!(player.is_pose("sliding"));
+		assert_eq!(player.params.rotation, world.articles["Player"].params.rotation);
+		assert_ne!(player.params.rotation, 0.0);
+		assert_eq!(restored.state_hash(), world.state_hash());
+	}
+}
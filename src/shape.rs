@@ -0,0 +1,133 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
+pub mod shape {
+	use macroquad::math::{vec2, Rect, Vec2};
+
+	/**
+	 * Convex polygon in article space, points going clockwise on screen (y down).
+	 * Used for slopes and other surfaces a rect can't describe
+	 */
+	#[derive(Clone, Debug, PartialEq)]
+	pub struct Polygon {
+		pub points: Vec<Vec2>,
+	}
+
+	/**
+	 * Smallest push that separates two shapes, normal points away from the shape being pushed against
+	 */
+	#[derive(Clone, Copy, Debug, PartialEq)]
+	pub struct Penetration {
+		pub normal: Vec2,
+		pub depth: f32,
+	}
+
+	fn project(points: &[Vec2], axis: Vec2) -> (f32, f32) {
+		points.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), point| {
+			let distance = point.dot(axis);
+			(min.min(distance), max.max(distance))
+		})
+	}
+
+	fn corners(rect: &Rect) -> [Vec2; 4] {
+		[rect.point(), vec2(rect.right(), rect.y), vec2(rect.right(), rect.bottom()), vec2(rect.x, rect.bottom())]
+	}
+
+	impl Polygon {
+		pub fn new(points: Vec<Vec2>) -> Self {
+			Self { points }
+		}
+
+		pub fn offset(&self, offset: Vec2) -> Polygon {
+			Polygon::new(self.points.iter().map(|point| *point + offset).collect())
+		}
+
+		pub fn bounding_box(&self) -> Rect {
+			let (min, max) = self.points.iter().fold((Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)), |(min, max), point| {
+				(min.min(*point), max.max(*point))
+			});
+			Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+		}
+
+		fn center(&self) -> Vec2 {
+			self.points.iter().fold(Vec2::ZERO, |sum, point| sum + *point) / self.points.len().max(1) as f32
+		}
+
+		/**
+		 * Mirror across the middle of an article of the given width, keeping the points in order
+		 */
+		pub fn mirror_x(&mut self, width: f32) {
+			for point in self.points.iter_mut() {
+				point.x = width - point.x;
+			}
+			self.points.reverse();
+		}
+
+		pub fn mirror_y(&mut self, height: f32) {
+			for point in self.points.iter_mut() {
+				point.y = height - point.y;
+			}
+			self.points.reverse();
+		}
+
+		/**
+		 * Separating axis test against a rect, None unless they overlap
+		 */
+		pub fn penetration(&self, rect: &Rect) -> Option<Penetration> {
+			if self.points.len() < 3 {
+				return None;
+			}
+			let rect_points = corners(rect);
+			let edge_normals = self.points.iter().zip(self.points.iter().cycle().skip(1))
+				.map(|(a, b)| (*b - *a).perp().normalize_or_zero());
+			let mut deepest: Option<Penetration> = None;
+			for axis in [Vec2::X, Vec2::Y].into_iter().chain(edge_normals) {
+				if axis == Vec2::ZERO {
+					continue;
+				}
+				let (poly_min, poly_max) = project(&self.points, axis);
+				let (rect_min, rect_max) = project(&rect_points, axis);
+				let depth = poly_max.min(rect_max) - poly_min.max(rect_min);
+				if depth <= 0.0 {
+					return None;	//Found a gap, so they don't overlap
+				}
+				if deepest.is_none_or(|deepest| depth < deepest.depth) {
+					deepest = Some(Penetration { normal: axis, depth });
+				}
+			}
+			deepest.map(|mut penetration| {
+				if (rect.center() - self.center()).dot(penetration.normal) < 0.0 {
+					penetration.normal = -penetration.normal;
+				}
+				penetration
+			})
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::shape::Polygon;
+	use macroquad::math::{vec2, Rect};
+
+	fn ramp() -> Polygon {
+		//Rises from left to right at 45 degrees
+		Polygon::new(vec![vec2(0.0, 100.0), vec2(100.0, 0.0), vec2(100.0, 100.0)])
+	}
+
+	#[test]
+	fn rect_resting_into_a_slope_is_pushed_out_along_its_normal() {
+		let penetration = ramp().penetration(&Rect::new(40.0, 40.0, 10.0, 15.0)).unwrap();
+		let expected = vec2(-1.0, -1.0).normalize();
+		assert!((penetration.normal - expected).length() < 1e-5, "normal {}", penetration.normal);
+		assert!((penetration.depth - 5.0 / 2.0f32.sqrt()).abs() < 1e-3, "depth {}", penetration.depth);
+
+		assert_eq!(ramp().penetration(&Rect::new(10.0, 10.0, 20.0, 20.0)), None);
+	}
+
+	#[test]
+	fn mirroring_keeps_the_slope_solid() {
+		let mut ramp = ramp();
+		ramp.mirror_x(100.0);
+		let penetration = ramp.penetration(&Rect::new(50.0, 40.0, 10.0, 15.0)).unwrap();
+		assert!(penetration.normal.x > 0.0 && penetration.normal.y < 0.0);
+	}
+}
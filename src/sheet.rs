@@ -0,0 +1,97 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
+pub mod sheet {
+	use std::collections::HashMap;
+	use macroquad::prelude::*;
+	use serde::Deserialize;
+
+	/**
+	 * Named points on one frame of a spritesheet, in source pixels from the frame's top left
+	 */
+	#[derive(Deserialize)]
+	pub struct FrameDef {
+		pub column: u32,
+		#[serde(default)]
+		pub row: u32,
+		pub anchors: HashMap<String, (f32, f32)>,
+	}
+
+	/**
+	 * Metadata that sits alongside a spritesheet texture
+	 */
+	#[derive(Default, Deserialize)]
+	pub struct SpriteSheet {
+		pub frames: Vec<FrameDef>,
+	}
+
+	impl SpriteSheet {
+		pub fn parse(source: &str) -> Result<SpriteSheet, ron::error::SpannedError> {
+			ron::from_str(source)
+		}
+
+		pub async fn load(path: &str) -> Option<SpriteSheet> {
+			let source = match load_string(path).await {
+				Ok(s) => s,
+				Err(e) => {
+					println!("Could not load spritesheet: {}", e);
+					return None;
+				}
+			};
+			match SpriteSheet::parse(&source) {
+				Ok(sheet) => Some(sheet),
+				Err(e) => {
+					println!("Could not parse spritesheet {}: {}", path, e);
+					None
+				}
+			}
+		}
+
+		/**
+		 * Read straight from disk, for running without a window
+		 */
+		pub fn read(path: &str) -> Result<SpriteSheet, String> {
+			let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+			SpriteSheet::parse(&source).map_err(|e| e.to_string())
+		}
+
+		/**
+		 * Anchor on the given frame, unflipped and in source pixels
+		 */
+		pub fn anchor(&self, column: u32, row: u32, name: &str) -> Option<Vec2> {
+			self.frames.iter()
+				.find(|frame| frame.column == column && frame.row == row)
+				.and_then(|frame| frame.anchors.get(name))
+				.map(|(x, y)| vec2(*x, *y))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::sheet::SpriteSheet;
+	use crate::article::article::Article;
+	use macroquad::prelude::*;
+	use std::rc::Rc;
+
+	const SHEET: &str = r#"(
+		frames: [
+			(column: 0, anchors: { "hand": (10.0, 20.0) }),
+			(column: 1, anchors: { "hand": (30.0, 40.0) }),
+		],
+	)"#;
+
+	#[test]
+	fn anchor_follows_frame_scale_and_flip() {
+		let sheet = Rc::new(SpriteSheet::parse(SHEET).unwrap());
+		let mut article = Article::new(Rect::new(0.0, 0.0, 100.0, 100.0), Rect::new(1000.0, 500.0, 200.0, 200.0), None);
+		article.sheet = Some(sheet);
+
+		assert_eq!(article.anchor("hand"), Some(vec2(1020.0, 540.0)));
+		assert_eq!(article.anchor("foot"), None);
+
+		article.set_frame(vec2(1.0, 0.0));
+		assert_eq!(article.anchor("hand"), Some(vec2(1060.0, 580.0)));
+
+		article.set_direction(Vec2::X);
+		assert_eq!(article.anchor("hand"), Some(vec2(1140.0, 580.0)));
+	}
+}
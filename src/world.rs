@@ -1,576 +1,1246 @@
+#[allow(clippy::module_inception, clippy::tabs_in_doc_comments)]
 pub mod world {
-	use crate::article::article::{Article, CollisionResult};
-	use std::collections::HashMap;
-	use macroquad::prelude::*;
-	
-	pub async fn cached_texture(article: &mut Article, texture: Result<&Texture2D, &str>) -> Option<Texture2D> {
-		match texture {
-			Ok(texture) => article.texture = Some(texture.to_owned()),
-			Err(texture_filepath) => {
-				return article.load_texture(texture_filepath).await;
+	use crate::article::article::{Article, Event, OutOfBounds};
+	use crate::background::background::Background;
+	use crate::behaviour::behaviour;
+	use crate::broadphase::broadphase::SpatialGrid;
+	use crate::component::component::HookState;
+	use crate::input::input::InputState;
+	use crate::level::level::{Level, to_rect};
+	use crate::replay::replay::StableHasher;
+	use crate::save::save::{ArticleState, SaveGame};
+	use crate::sheet::sheet::SpriteSheet;
+	use std::{collections::HashMap, rc::Rc};
+	use macroquad::{math::{vec2, Rect, Vec2}, texture::Texture2D};
+
+	/**
+	 * Every article in the current level along with the order they're updated in.
+	 * Stepping the world touches no window or GPU state, so it can run headless
+	 */
+	pub struct World {
+		pub name: String,
+		pub articles: HashMap<String, Article>,
+		article_keys: Vec<String>,
+		active_keys: Vec<String>,	//Every article that isn't static, in update order
+		events: Vec<Event>,
+		grid: SpatialGrid,	//Kept in sync with article positions, used to find collision candidates
+		spawn: Vec2,	//Where the player comes back after dying or falling, moved by checkpoints
+		originals: HashMap<String, Article>,	//Articles that reset on respawn, as the level built them
+		starts: HashMap<String, Vec2>,	//Where the level placed each article that can move
+		pub bounds: Option<Rect>,	//Articles leaving this area are handled by their out of bounds policy
+		pub background: Background,
+		player_dead: bool,
+	}
+
+	/**
+	 * The world always advances in steps of DT seconds no matter how fast frames are drawn
+	 */
+	pub const TICK_RATE: f32 = 60.0;
+	pub const DT: f32 = 1.0 / TICK_RATE;
+
+	pub const GRAVITY: f32 = 1440.0;	//Pixels per second squared
+	const AIR_DRAG: f32 = 0.3;	//Fraction of velocity lost per second
+	const GRID_CELL_SIZE: f32 = 256.0;	//Pixels, around the size of the larger moving articles
+
+	/**
+	 * Apply the article's out of bounds policy, true if the player needs to go back to the last checkpoint.
+	 * The player keeps whatever health the policy left it with
+	 */
+	fn leave_bounds(article: &mut Article, start: Option<Vec2>) -> bool {
+		let is_player = article.name == "Player";
+		match article.out_of_bounds {
+			OutOfBounds::Destroy => article.do_destroy = true,
+			OutOfBounds::Kill => match &mut article.components.health {
+				Some(health) => health.current = 0.0,
+				None => article.do_destroy = true
+			},
+			OutOfBounds::Damage(amount) => {
+				if let Some(health) = &mut article.components.health {
+					health.invulnerable_for = 0.0;
+				}
+				article.update_health(-amount);
+				if !is_player {
+					respawn_at(article, start);
+				}
+				return is_player;
+			},
+			OutOfBounds::Respawn => {
+				if !is_player {
+					respawn_at(article, start);
+				}
+				return is_player;
+			},
+			OutOfBounds::Ignore => ()
+		}
+		false
+	}
+
+	fn respawn_at(article: &mut Article, start: Option<Vec2>) {
+		if let Some(start) = start {
+			article.pos = start;
+			article.prev_pos = start;
+			article.vel = Vec2::ZERO;
+			article.attached = None;
+		}
+	}
+
+	fn global_forces(article: &mut Article) {
+		if article.mass.is_finite() {
+			//Gravity
+			article.vel.y += GRAVITY * DT;
+			//Atmospheric Drag - Air Resistance
+			article.vel -= article.vel * AIR_DRAG * DT;
+		}
+	}
+
+	/**
+	 * Buoyancy and drag from any water the article overlaps, scaled by its mass.
+	 * Crossing the surface either way raises a splash
+	 */
+	fn water_forces(article: &mut Article, articles: &HashMap<String, Article>, grid: &SpatialGrid) {
+		let (Some(bounds), Some(area)) = (&article.bounds, article.bounding_box()) else {
+			return;
+		};
+		let bounds: Vec<Rect> = bounds.iter().map(|bound| bound.offset(article.pos)).collect();
+		let volume: f32 = bounds.iter().map(|bound| bound.w * bound.h).sum();
+		let mut in_water = None;
+		let mut submerged = 0.0;
+		for name in grid.query(area) {
+			let Some((water, water_article)) = articles.get(name).and_then(|other| other.components.water.as_ref().map(|water| (water, other))) else {
+				continue;
+			};
+			let overlap: f32 = bounds.iter()
+				.flat_map(|bound| water_article.bounds.iter().flatten().filter_map(move |water_bound| {
+					bound.intersect(water_bound.offset(water_article.pos))
+				}))
+				.map(|overlap| overlap.w * overlap.h)
+				.sum();
+			if overlap <= 0.0 {
+				continue;
+			}
+			article.vel.y -= GRAVITY * water.density * overlap / article.mass * DT;
+			article.vel -= article.vel * (water.drag * overlap / volume / article.mass * DT).min(1.0);
+			submerged += overlap / volume;
+			in_water.get_or_insert_with(|| name.to_string());
+		}
+
+		if in_water.is_some() != article.in_water.is_some() {
+			let surface = in_water.as_ref().or(article.in_water.as_ref())
+				.and_then(|name| articles.get(name))
+				.and_then(|water| water.bounding_box())
+				.map_or(area.y, |water| water.y);
+			article.events.push(Event::Splash(vec2(area.center().x, surface)));
+		}
+		article.in_water = in_water;
+		article.submerged = submerged;
+	}
+
+	impl World {
+		pub fn new(name: String, articles: HashMap<String, Article>) -> Self {
+			let mut grid = SpatialGrid::new(GRID_CELL_SIZE);
+			for (key, article) in articles.iter() {
+				if let Some(area) = article.bounding_box() {
+					grid.update(key, area);
+				}
+			}
+			let spawn = articles.get("Player").map_or(Vec2::ZERO, |player| player.pos);
+			let originals = articles.iter()
+				.filter(|(_, article)| article.reset_on_respawn)
+				.map(|(key, article)| (key.clone(), article.clone()))
+				.collect();
+			let starts = articles.iter()
+				.filter(|(_, article)| !article.is_static())
+				.map(|(key, article)| (key.clone(), article.pos))
+				.collect();
+			let mut world = Self {
+				name,
+				articles,
+				article_keys: Vec::new(),
+				active_keys: Vec::new(),
+				events: Vec::new(),
+				grid,
+				spawn,
+				originals,
+				starts,
+				bounds: None,
+				background: Background::default(),
+				player_dead: false,
+			};
+			world.sort_keys();
+			world
+		}
+
+		fn sort_keys(&mut self) {
+			self.article_keys = self.articles.keys().cloned().collect();
+			self.article_keys.sort();
+			self.active_keys = self.article_keys.iter()
+				.filter(|key| !self.articles[*key].is_static())
+				.cloned()
+				.collect();
+		}
+
+		/**
+		 * Build the world described by a level file, binding textures and behaviours by name
+		 */
+		pub async fn load(level_path: &str) -> Self {
+			match Level::load(level_path).await {
+				Some(level) => {
+					let textures = level.load_textures().await;
+					let sheets = level.load_sheets().await;
+					Self::from_level(&level, &textures, &sheets)
+				},
+				None => Self::new(String::new(), HashMap::new())
 			}
 		}
-		None
-	}
-
-	pub async fn load_articles() -> HashMap<String, Article> {
-		let mut texture_map = HashMap::<&str, Texture2D>::new();
-		let mut articles = HashMap::<String, Article>::new();
-		
-		let mut player = Article::new(
-			Rect::new(0.0, 0.0, 315.0, 480.0), 
-			Rect::new(2200.0,-200.0,90.0,140.0), 
-			Some(vec![Rect::new(25.0, 20.0, 40.0, 88.0)])
-		);
-		let texture_filepath = "res/textures/penguin.png";
-		if let Some(texture) = cached_texture(&mut player, texture_map.get(texture_filepath).ok_or(texture_filepath)).await {
-			texture_map.insert(texture_filepath, texture);
-		}
-		player.name = "Player".to_string();
-		player.mass = 5.0;
-		player.elasticity = 0.5;
-		player.cog = vec2(44.5, 66.0);
-		player.scratchpad.insert("avail_health".to_string(), 5.0);
-		player.scratchpad.insert("health".to_string(), 5.0);
-
-
-		player.tick = Some(|player, _articles| {
-			
-			
-			{	//Allow Zooming in and out of the screen
-				let (_, mouse_wheel_y) = mouse_wheel();
-				if mouse_wheel_y != 0.0 {
-					if let Some(z) = player.scratchpad.get_mut("zoom") {
-						*z *= 1.1f32.powf(mouse_wheel_y/mouse_wheel_y.abs());
+
+		pub fn from_level(level: &Level, textures: &HashMap<String, Texture2D>, sheets: &HashMap<String, Rc<SpriteSheet>>) -> Self {
+			let articles = level.build_articles(textures, sheets, &behaviour::registry());
+			let mut world = Self::new(level.name.clone(), articles);
+			world.bounds = level.bounds.as_ref().map(to_rect);
+			world.background = Background::new(&level.background, textures);
+			world
+		}
+
+		/**
+		 * Advance every article by one fixed step of DT seconds. Each phase runs over the whole world
+		 * before the next starts, so nothing collides against an article that hasn't had its tick yet:
+		 * tick applies forces and behaviour, physics moves and collides, then post physics hooks see
+		 * where everything ended up. Input is gathered before the step and rendering happens after it
+		 */
+		pub fn step(&mut self, input: &InputState) {
+			let destroyed = self.tick_phase(input);
+			let respawn = self.physics_phase();
+			self.post_physics_phase();
+			self.finish_step(destroyed, respawn);
+		}
+
+		/**
+		 * Forces and behaviour ticks, true if any article was destroyed
+		 */
+		fn tick_phase(&mut self, input: &InputState) -> bool {
+			let mut destroyed = false;
+			for index in self.active_keys.iter() {
+				if let Some(mut article) = self.articles.remove(index) {
+					if !article.do_destroy {
+						article.prev_pos = article.pos;
+						if article.mass.is_finite() {
+							global_forces(&mut article);
+							water_forces(&mut article, &self.articles, &self.grid);
+						}
+						article.tick(&mut self.articles, &self.grid, input);
+						self.articles.insert(index.clone(), article);
 					} else {
-						player.scratchpad.insert("zoom".to_string(), 0.0008);
+						//If do destroy is set, article is dereferenced and freed
+						self.grid.remove(index);
+						destroyed = true;
 					}
 				}
 			}
+			destroyed
+		}
 
-			if is_key_down(KeyCode::R) {
-				player.pos = Vec2::ZERO;
+		/**
+		 * Move and collide every article, true if the player needs to go back to the last checkpoint
+		 */
+		fn physics_phase(&mut self) -> bool {
+			let mut respawn = false;
+			//Ticks can move articles too, like a platform turning at the end of its track, so nothing collides against where they were
+			for index in self.active_keys.iter() {
+				if let Some(area) = self.articles.get(index).and_then(|article| article.bounding_box()) {
+					self.grid.update(index, area);
+				}
 			}
-			if is_key_down(KeyCode::S) ||  is_key_down(KeyCode::Down) {
-				player.params.rotation = std::f32::consts::PI / 2.0;
-				if player.friction_coefficient == 0.85 {
-					if let Some(new_dest_size) = player.params.dest_size {
-						player.params.dest_size = Some(vec2(new_dest_size.y, new_dest_size.x));
+			for index in self.active_keys.iter() {
+				if let Some(mut article) = self.articles.remove(index) {
+					if article.mass.is_finite() {
+						article.calculate_collisions(&mut self.articles, &self.grid);
 					}
-					player.vel.x*=1.5;	//Dash once when on belly
-				}
-				player.friction_coefficient = 0.99999;	//Update to new friction coefficient to allow sliding
-			} else {
-				player.params.rotation = 0.0;
-				if player.friction_coefficient == 0.99999 {
-					if let Some(new_dest_size) = player.params.dest_size {
-						player.params.dest_size = Some(vec2(new_dest_size.y, new_dest_size.x));
+					let area = article.bounding_box().unwrap_or(Rect::new(article.pos.x, article.pos.y, 0.0, 0.0));
+					if self.bounds.is_some_and(|bounds| !bounds.overlaps(&area)) {
+						respawn |= leave_bounds(&mut article, self.starts.get(index).copied());
 					}
-				}
-				player.friction_coefficient = 0.85;
-
-				if is_key_down(KeyCode::A) ||  is_key_down(KeyCode::Left) {
-					if let Some(_) = player.attached {
-						player.vel.x -= 2.0;
-					} else if player.vel.x < 0.0 {
-						player.vel.x -= 0.2;
-					} else if player.vel.x >= 0.0 {
-						player.vel.x -= 2.0;
+					if let Some(area) = article.bounding_box() {
+						self.grid.update(index, area);
 					}
-					player.set_direction(-Vec2::X);
+					self.articles.insert(index.clone(), article);
 				}
-				else if is_key_down(KeyCode::D) ||  is_key_down(KeyCode::Right) {
-					if let Some(_) = player.attached {
-						player.vel.x += 2.0;
-					} else if player.vel.x > 0.0 {
-						player.vel.x += 0.2;
-					} else if player.vel.x <= 0.0 {
-						player.vel.x += 2.0;
+			}
+			respawn
+		}
+
+		/**
+		 * Hooks for behaviours that follow other articles, run once every article has moved
+		 */
+		fn post_physics_phase(&mut self) {
+			for index in self.active_keys.iter() {
+				let Some(post_physics) = self.articles.get(index).and_then(|article| article.post_physics) else {
+					continue;
+				};
+				if let Some(mut article) = self.articles.remove(index) {
+					(post_physics)(&mut article, &mut self.articles);
+					if let Some(area) = article.bounding_box() {
+						self.grid.update(index, area);
 					}
-					player.set_direction(Vec2::X);
+					self.articles.insert(index.clone(), article);
 				}
 			}
-			if is_key_down(KeyCode::Space) ||is_key_down(KeyCode::W) ||  is_key_down(KeyCode::Up) {
-				if let Some(_attachment) = &player.attached {
-					player.vel.y = -23.0;
-				}
-				//player.remove_attachment(articles);
+		}
+
+		/**
+		 * Drop destroyed articles and hand their events on
+		 */
+		fn finish_step(&mut self, destroyed: bool, mut respawn: bool) {
+			if destroyed {
+				self.article_keys.retain(|key| self.articles.contains_key(key));
+				self.active_keys.retain(|key| self.articles.contains_key(key));
 			}
 
-			//If player is hooked, they cannot move while the damage indicator blinks
-			if let Some(hooked) = player.scratchpad.get("hooked") {
-				if *hooked == 1.0 {
-					player.vel = Vec2::ZERO;
-				}
-				//If hooked, player should've been damaged
-				if let Some(most_recent_damage_time) = player.scratchpad.get("most_recent_damage") {
-					//After 2 seconds, fisherman animation should've finished and player can be yeeted
-					
+			//Static articles have no behaviour that could raise events
+			let mut raised = Vec::new();
+			for index in self.active_keys.iter() {
+				if let Some(article) = self.articles.get_mut(index) {
+					raised.append(&mut article.events);
 				}
 			}
-		});
-		player.do_collide = Some(|axis, a, b, intersection| {
-			if axis.x == 1.0 {
-				if let Some(attachment) = &a.attached {
-					if attachment.as_str() == b.name.as_str() {
-						return CollisionResult::DontPropagate(-10);
-					}
+			for event in raised.iter() {
+				match event {
+					Event::CheckpointReached(spawn) => self.spawn = *spawn,
+					Event::Respawn => respawn = true,	//Asked for, so it isn't a way to heal
+					_ => ()
 				}
 			}
-			match b.do_collide {
-				Some(collide_func) => collide_func(axis, b, a, intersection),
-				None => Article::default_collide(axis, a, b, intersection)
+			//Raised once however the player died, a fall counts the same as running out of health
+			if self.player_defeated() && !self.player_dead {
+				self.player_dead = true;
+				raised.push(Event::PlayerDied);
 			}
-		});
-		articles.insert(player.name.clone(), player);
-
-
-
-		for i in 0..5 {
-			//Platform 
-			let mut platform = Article::new(
-				Rect::new(0.0, 100.0, 400.0, 120.0),
-				Rect::new(3000.0 + (i as f32*480.00),(i as f32 * 300.0),400.0,120.0), 
-				Some(vec![Rect::new(0.0, 60.0, 400.0, 60.0)])
-			);
-			platform.name = format!("Platform-{}", i);
-			
-			let texture_filepath = "res/textures/grass.png";
-			if let Some(texture) = cached_texture(&mut platform, texture_map.get(texture_filepath).ok_or(texture_filepath)).await {
-				texture_map.insert(texture_filepath, texture);
+			self.events.append(&mut raised);
+			//A fall that killed the player is left for the game over screen to respawn from
+			if respawn && !self.player_dead {
+				self.respawn(false);
 			}
-			platform.mass = 100_000_000.0;
-			platform.elasticity = 0.0;
-			platform.vel.y = 0.0;
-			platform.tick = Some(|platform, articles| {
-				platform.remove_attachment(articles);
-				platform.vel.x = 0.0;
-				if platform.vel.y >= 0.0 {
-					platform.vel.y += 2.0;
-					platform.attached_to = vec![];
-				} else {
-					platform.vel.y = -3.0;
-				}
-				if -100.0 > platform.pos.y {
-					platform.pos.y = -100.0;
-					platform.vel.y = 1.0;
-				}
-				if platform.pos.y > 400.0 {
-					platform.pos.y = 400.0;
-					platform.vel.y = -3.0;
+		}
+
+		/**
+		 * Put the player back at the last checkpoint and reset any articles marked to reset.
+		 * Health is only refilled when coming back from a death
+		 */
+		pub fn respawn(&mut self, refill_health: bool) {
+			self.player_dead = false;
+			if let Some(mut player) = self.articles.remove("Player") {
+				player.remove_attachment(&mut self.articles);
+				player.pos = self.spawn;
+				player.prev_pos = self.spawn;
+				player.vel = Vec2::ZERO;
+				if let (Some(health), true) = (&mut player.components.health, refill_health) {
+					health.current = health.max;
+					health.invulnerable_for = 0.0;
 				}
-			});
-			platform.do_collide = Some(|axis, a, b, intersection| {
-				if axis.x == 1.0 {
-					a.vel.x = 0.0;
-					if let Some(attachment) = &b.attached {
-						if attachment.as_str() == a.name.as_str() {
-							return CollisionResult::DontPropagate(-10);
-						}
-					}
+				if let Some(hookable) = &mut player.components.hookable {
+					hookable.state = HookState::Free;
 				}
-				if axis.y == 1.0 {
-					if a.pos.y > b.pos.y {
-						b.vel.y -= a.vel.y;
-					}
+				if let Some(area) = player.bounding_box() {
+					self.grid.update("Player", area);
 				}
-				let collision_result = Article::flat_collide(axis, b, a, intersection);
-				if axis.y == 1.0 {
-					if a.name.contains("Platform") {
-						if a.pos.y > b.pos.y {
-							b.attached = Some(a.name.clone());
-							if !a.attached_to.contains(&b.name) {
-								a.attached_to.push(b.name.clone());
-							}
-						} else {
-							b.attached = None;
-							a.attached_to.retain(|e| e.as_str() == b.name.as_str());
-							b.vel.y = 1.0;
-							if a.vel.y > 0.0 {
-								a.vel.y = -0.1;
-							}
-						}
-					}
+				self.articles.insert(player.name.clone(), player);
+			}
+
+			let restored = self.originals.keys().any(|key| !self.articles.contains_key(key));
+			for (key, original) in self.originals.iter() {
+				if let Some(area) = original.bounding_box() {
+					self.grid.update(key, area);
 				}
-				collision_result
-			});
-			articles.insert(platform.name.clone(), platform);
-		}
-		
-		/*Icy Ground */
-		for x in -2..2 {
-			let mut article = Article::new(
-				Rect::new(0.0, 0.0, 6184.0, 2048.0), 
-				Rect::new(6184.0 * x as f32,500.0,6184.0,2048.0), 
-				Some(vec![Rect::new(0.0, 56.0, 6184.0, 1992.0)])
-			);
-			article.name = format!("Grass-{}-{}", x, 0);
-
-			let texture_filepath = "res/textures/snow.png";
-			if let Some(texture) = cached_texture(&mut article, texture_map.get(texture_filepath).ok_or(texture_filepath)).await {
-				texture_map.insert(texture_filepath, texture);
+				self.articles.insert(key.clone(), original.clone());
 			}
-			article.mass = f32::INFINITY;
-			article.elasticity = 0.0;
-			articles.insert(article.name.clone(), article);
-		}
-
-		for x in 12..20 {
-			let mut block = Article::new(
-				Rect::new(0.0, 0.0, 64.0, 64.0), 
-				Rect::new(200.0 * x as f32,-100.0,64.0,64.0), 
-				Some(vec![Rect::new(0.0, 0.0, 64.0, 64.0)])
-			);
-			block.name = format!("Block-{}", x);
-			let texture_filepath = "res/textures/crate.png";
-			if let Some(texture) = cached_texture(&mut block, texture_map.get(texture_filepath).ok_or(texture_filepath)).await {
-				texture_map.insert(texture_filepath, texture);
+			if restored {
+				self.sort_keys();
 			}
-			block.mass = 1.0;
-			block.elasticity = 1.0;
-			block.friction_coefficient = 0.9;
-			block.do_collide = Some(|axis, a, b, intersection| {
-				if axis.x == 1.0 {
-					if let Some(attachment) = &a.attached {
-						if attachment.as_str() == b.name.as_str() {
-							return CollisionResult::DontPropagate(-10);
-						}
-					}
-					return Article::elastic_collide(axis, a, b, intersection);
-				}
-				if a.pos.y < b.pos.y {
-					a.attached = Some(b.name.clone());
-					Article::flat_collide(axis, a, b, intersection)
-				} else {
-					b.attached = Some(a.name.clone());
-					Article::flat_collide(axis, b, a, intersection)
-				}
-			});
-			articles.insert(block.name.clone(), block);
 		}
-		
-
-		for i in 1..4 {
-			let x_index = i as f32 * 400.0;
-			//Enemy
-			let mut enemy = Article::new(
-				Rect::new(0.0, 0.0, 256.0, 128.0), 
-				Rect::new(x_index,0.0,-300.0,128.0), 
-				Some(vec![Rect::new(47.0, 33.0, 129.0, 62.0)])
-			);
-			let texture_filepath = "res/textures/spider.png";
-			if let Some(texture) = cached_texture(&mut enemy, texture_map.get(texture_filepath).ok_or(texture_filepath)).await {
-				texture_map.insert(texture_filepath, texture);
-			}
-			enemy.name = format!("Enemy-{}", i);
-			enemy.scratchpad.insert("x-index".to_string(), x_index);
-			enemy.mass = 1000000.0;
-			enemy.elasticity = 1.0;
-			enemy.vel.x = 20.0;
-			enemy.tick = Some(|enemy, articles| {
-				if enemy.vel.x >= 0.0 {
-					enemy.vel.x = 6.0;
-					enemy.set_direction(Vec2::X);
-				} else {
-					enemy.vel.x = -6.0;
-					enemy.set_direction(-Vec2::X);
+
+		/**
+		 * Alpha is the fraction of a step that has elapsed since the last call to step
+		 */
+		pub fn draw(&mut self, alpha: f32) {
+			for index in self.draw_order() {
+				if let Some(article) = self.articles.get_mut(&index) {
+					article.draw(alpha);
 				}
-				if let Some(x_index) = enemy.scratchpad.get("x-index") {
-					if (-500.0 + x_index) > enemy.pos.x {
-						enemy.pos.x = -500.0 + x_index;
-						enemy.vel.x = 5.0;
+			}
+		}
+
+		/**
+		 * Keys of every article in the order they're drawn, by layer then z index.
+		 * The sort is stable over the name sorted keys, so ties are always drawn the same way round
+		 */
+		pub fn draw_order(&self) -> Vec<String> {
+			let mut keys = self.article_keys.clone();
+			keys.sort_by_key(|key| self.articles.get(key).map(|article| (article.layer, article.z)));
+			keys
+		}
+
+		/**
+		 * Events raised by articles since the last call
+		 */
+		pub fn take_events(&mut self) -> Vec<Event> {
+			std::mem::take(&mut self.events)
+		}
+
+		/**
+		 * Hash of everything the simulation changes that matters for comparing runs, in a stable order
+		 */
+		pub fn state_hash(&self) -> u64 {
+			let mut hasher = StableHasher::default();
+			for key in self.article_keys.iter() {
+				if let Some(article) = self.articles.get(key) {
+					hasher.write_str(key);
+					for value in [article.pos.x, article.pos.y, article.vel.x, article.vel.y, article.params.rotation] {
+						hasher.write_f32(value);
 					}
-					if enemy.pos.x > (1000.0 + x_index) {
-						enemy.pos.x = 1000.0 + x_index;
-						enemy.vel.x = -5.0;
+					if let Some(health) = &article.components.health {
+						hasher.write_f32(health.current);
 					}
-				}
-			});
-			enemy.do_collide = Some(|axis, a, b, intersection| {
-				if axis.y == 1.0 && b.vel.y > 0.1 {
-					a.do_destroy = true;
-				} else if b.name.contains("Player") {
-					b.update_health(-1.0);
-					a.scratchpad.insert("hidden".to_string(), 1.0);
-				}
-				let collision_result = Article::default_collide(axis, a, b, intersection);
-				if axis.x == 1.0 {
-					a.vel.x *= -1.0;
-				}
-				collision_result
-			});
-			articles.insert(enemy.name.clone(), enemy);
-		}
-
-		
-		//Fisherman
-		let mut fisherman = Article::new(
-			Rect::new(0.0, 0.0, 384.0, 512.0), 
-			Rect::new(1700.0, -500.0, 384.0, 512.0), 
-			Some(vec![
-				Rect::new(114.0, 358.0, 73.0, 98.0),	//Torso
-				Rect::new(132.0, 457.0, 41.0, 48.0),	//Legs
-				Rect::new(138.0, 309.0, 35.0, 49.0)		//Head
-			])
-		);
-		let texture_filepath = "res/textures/fisherman_spritesheet.png";
-		if let Some(texture) = cached_texture(&mut fisherman, texture_map.get(texture_filepath).ok_or(texture_filepath)).await {
-			texture_map.insert(texture_filepath, texture);
-		}
-		
-		fisherman.name = format!("fisherman-{}", 0);
-		fisherman.scratchpad.insert("x-index".to_string(), -100.0);
-		fisherman.scratchpad.insert("player-x".to_string(), -100.0);
-		fisherman.scratchpad.insert("player-x".to_string(), -100.0);
-		fisherman.scratchpad.insert("status".to_string(), 0.0);
-		fisherman.mass = 10_000_000.0;
-		fisherman.elasticity = 1.0;
-		fisherman.set_direction(Vec2::X);
-		
-
-	
-		let mut lure  = Article::new(
-			Rect::new(0.0, 0.0, 32.0, 32.0), 
-			Rect::new(100_00.0, 100_000.0,32.0,32.0), 
-			Some(vec![Rect::new(0.0, 0.0, 25.0, 25.0)])
-		);
-		lure.name = format!("lure-{}", fisherman.name.clone());
-		let texture_filepath = "res/textures/lure.png";
-		if let Some(texture) = cached_texture(&mut lure, texture_map.get(texture_filepath).ok_or(texture_filepath)).await {
-			texture_map.insert(texture_filepath, texture);
-		}
-		lure.elasticity = 0.5;
-		lure.mass = 1.0;
-		lure.friction_coefficient = 0.5;
-		lure.do_collide = Some(|axis, a, b, intersection| {
-			if a.name.contains("lure") {
-				let hidden = *(a.scratchpad.get("hidden").unwrap_or(&1.0));
-				let lure_hooked = *(a.scratchpad.get("hooked").unwrap_or(&0.0));
-
-				if hidden == 0.0 {
-					if lure_hooked < 2.0 {
-						if b.name.contains("Player") {
-							let player_hooked = *(b.scratchpad.get("hooked").unwrap_or(&0.0));
-							if player_hooked == 0.0 {
-								//Hurt player and mark both as hooked
-								b.update_health(-1.0);
-								b.scratchpad.insert("hooked".to_string(), 2.0);
-								a.scratchpad.insert("hooked".to_string(), 1.0);
-							}
-						}
-
-						if axis == Vec2::Y {
-							//Mark hook as landed on something
-							a.scratchpad.insert("hooked".to_string(), 1.0);
-						}
-					} else if b.name.contains("Player") {
-						return CollisionResult::DontPropagate(10);
+					match &article.attached {
+						Some(name) => hasher.write_str(name),
+						None => hasher.write(&[0]),
 					}
-					return Article::elastic_collide(axis, a, b, intersection);
 				}
 			}
-			return CollisionResult::DontPropagate(10);
-		});
-		lure.draw = Some(|lure| -> bool {
-			let hidden = *(lure.scratchpad.get("hidden").unwrap_or(&1.0));
-
-			if hidden == 0.0 {
-				let mut pole = lure.pos.clone();
-				if let Some(fisherman_x) = lure.scratchpad.get("fisherman-pole-x") {
-					pole.x = *fisherman_x;
-				}
-				if let Some(fisherman_y) = lure.scratchpad.get("fisherman-pole-y") {
-					pole.y = *fisherman_y;
-				}
-				draw_line(pole.x, pole.y, lure.pos.x+5.0, lure.pos.y+5.0, 1.0, BLACK);
+			hasher.finish()
+		}
+
+		/**
+		 * True once the player's health has run out
+		 */
+		pub fn player_defeated(&self) -> bool {
+			match self.articles.get("Player").and_then(|player| player.components.health.as_ref()) {
+				Some(health) => health.current <= 0.0,
+				None => false
 			}
-			return hidden == 0.0;
-		});
-		lure.tick = Some(|lure, articles| {
-			let hooked = *(lure.scratchpad.get("hooked").unwrap_or(&0.0));
-			if hooked == 0.0 {
-				if let Some(player) = articles.get_mut("Player") {
-					if (player.pos.x - lure.pos.x).abs() < 300.0 {
-						if lure.vel.abs().x > 1.0 {
-							//Need to calculate lure distance and remaining velocity
-							lure.vel.x *= 0.9 + (player.pos.x - lure.pos.x).abs() / (300.0 * lure.vel.abs().x);
-						}
-					}
+		}
+
+		/**
+		 * Copy the player's health from the level being left into this one
+		 */
+		pub fn carry_player_health(&mut self, from: &World) {
+			if let (Some(old_player), Some(new_player)) = (from.articles.get("Player"), self.articles.get_mut("Player")) {
+				if old_player.components.health.is_some() {
+					new_player.components.health = old_player.components.health.clone();
 				}
 			}
-		});
-		articles.insert(lure.name.clone(), lure);
-		
-
-		fn get_fisherman_lure_offset(fisherman: &mut Article) -> Vec2 {
-			if let Some(source) = fisherman.params.source {
-				let offsets = HashMap::from([
-					(0, 	vec2(245.0, 285.0)),
-					(384, 	vec2(244.0, 286.0)),
-					(768, 	vec2(244.0, 286.0)),
-					(1152,	vec2(289.0, 258.0)),
-					(1536,	vec2(189.0, 203.0)),
-					(1920,	vec2(103.0, 197.0)),
-				]);
-				let mut offset = *(offsets.get(&(source.x as i32)).unwrap_or(&Vec2::ZERO));
-				let dest = fisherman.params.dest_size.unwrap_or(Vec2::ZERO);
-				if fisherman.params.flip_x {
-					offset.x = dest.x - offset.x;
-				}
-				if fisherman.params.flip_y {
-					offset.y = dest.y - offset.y;
+		}
+
+		/**
+		 * Mutable state of every article still in the world, to be restored onto the level at level_path
+		 */
+		pub fn save_game(&self, level_path: &str) -> SaveGame {
+			SaveGame {
+				level: level_path.to_string(),
+				spawn: self.spawn,
+				player_dead: self.player_dead,
+				articles: self.articles.iter()
+					.map(|(key, article)| (key.clone(), ArticleState::capture(article)))
+					.collect(),
+			}
+		}
+
+		/**
+		 * Put a freshly loaded level back the way it was saved. Articles the save doesn't have were destroyed
+		 */
+		pub fn restore(&mut self, save: &SaveGame) {
+			self.spawn = save.spawn;
+			self.player_dead = save.player_dead;
+			self.events.clear();
+			self.articles.retain(|key, _| save.articles.contains_key(key));
+			for (key, state) in save.articles.iter() {
+				match self.articles.get_mut(key) {
+					Some(article) => state.apply(article),
+					None => println!("Saved article {} is not in the level, skipping it", key)
 				}
-				return offset;
 			}
-			return Vec2::ZERO;
-		}
-		fisherman.tick = Some(|fisherman: &mut Article, articles| {
-			let mut player_hooked = false;
-			if let Some(s) = fisherman.scratchpad.remove("status") {
-				let mut status = s;
-				if let Some(player) = articles.get_mut("Player") {
-					let p_h = *(player.scratchpad.get("hooked").unwrap_or(&0.0));
-					player_hooked = p_h == 1.0;
-					//Can use hooked as 2 to notify to restart the reeling animation
-					if p_h == 2.0 {
-						player.scratchpad.insert("hooked".to_string(), 1.0);
-						status = 1000.0
-					}
- 					if status == 1050.0 && player_hooked {
-						//Reeled in and yeeted
-						player.vel.y -= 20.0;
-						player.scratchpad.remove("hooked");
-					}
-					if player.pos.distance(fisherman.pos) < 1800.0 {
-						status += 1.0;
-						if status == 90.0 {
-							//Start Casting
-							fisherman.scratchpad.insert("player-x".to_string(), player.pos.x);
-							fisherman.scratchpad.insert("player-y".to_string(), player.pos.y);
-						}
-					} else {
-						status = 0.0;
-						player.scratchpad.insert("hooked".to_string(), 0.0);
-					}
+			self.grid = SpatialGrid::new(GRID_CELL_SIZE);
+			for (key, article) in self.articles.iter() {
+				if let Some(area) = article.bounding_box() {
+					self.grid.update(key, area);
 				}
+			}
+			self.sort_keys();
+		}
+	}
+}
 
-				let lure_name = format!("lure-{}", fisherman.name.clone());
-				if let Some(lure) = articles.get_mut(lure_name.as_str()) {
-					let hooked = *(lure.scratchpad.get("hooked").unwrap_or(&0.0));
-					if hooked > 0.0 {
-						if player_hooked {
-							if status < 1000.0 {
-								fisherman.set_frame(vec2(4.0, 0.0));//Set fisherman frame to reeling back and forth
-							}
-							//Change back and forth between 2 consecutive frames
-							let frame_direction = if (status as i32) % 30 == 0 { Vec2::X } else { -Vec2::X };
-							//Update animation every 15 frames
-							if (status as i32) % 15 == 0 {
-								fisherman.increment_frame(frame_direction);
-							}
-						}
+#[cfg(test)]
+#[allow(clippy::tabs_in_doc_comments)]
+mod tests {
+	use super::world::{World, TICK_RATE};
+	use crate::article::article::{Article, Event};
+	use crate::behaviour::behaviour;
+	use crate::input::input::{Action, InputConfig, InputState};
+	use crate::level::level::Level;
+	use crate::material::material::Material;
+	use crate::sheet::sheet::SpriteSheet;
+	use macroquad::prelude::*;
+	use std::{collections::HashMap, rc::Rc};
 
-						if status < 1000.0 {
-							status = 1000.0;
-						}
+	const GROUND: &str = r#"
+		(
+			name: "Ground",
+			src: (0.0, 0.0, 6184.0, 2048.0),
+			dest: (-3000.0, 500.0, 6184.0, 2048.0),
+			bounds: [(0.0, 56.0, 6184.0, 1992.0)],
+			mass: inf,
+			elasticity: 0.0,
+		),"#;
 
-						//Update fishing line to connect to end of pole
-						let lure_offset = get_fisherman_lure_offset(fisherman);
-						lure.scratchpad.insert("fisherman-pole-x".to_string(), fisherman.pos.x + lure_offset.x);
-						lure.scratchpad.insert("fisherman-pole-y".to_string(), fisherman.pos.y + lure_offset.y);
-
-						
-						if status > 1050.0 {
-							if !player_hooked  {
-								fisherman.set_frame(vec2(4.0, 0.0));//Set fisherman frame to reeling back and forth
-							} else {
-								lure.scratchpad.insert("hooked".to_string(), 2.0);
-							}
-							//50 frames before lure is reeled in
-							lure.vel = ((fisherman.pos + lure_offset) - lure.pos).normalize()*10.0;
-						} else {
-							lure.vel = Vec2::ZERO;//Do not move lure until damage indication is finished
-						}
-						if status > 1120.0 {
-							status = 0.0;
-							fisherman.set_frame(Vec2::ZERO);
-							lure.scratchpad.remove("hooked");
-							lure.scratchpad.insert("hidden".to_string(), 1.0);
-						}
-					} else {
-						if status == 100.0  {
-							//Position lure above fisherman
-							lure.pos.x = fisherman.pos.x + 281.0;
-							lure.pos.y = fisherman.pos.y + 197.0;
-							lure.vel.y = -15.0;
-							//Calculate x vel to run into player
-							if let Some(player_x) = fisherman.scratchpad.get("player-x") {
-								lure.vel.x = (*player_x - lure.pos.x)/50.0;
-							}
-							let offset = get_fisherman_lure_offset(fisherman);
-							lure.scratchpad.insert("fisherman-pole-x".to_string(), fisherman.pos.x + offset.x);
-							lure.scratchpad.insert("fisherman-pole-y".to_string(), fisherman.pos.y + offset.y);
-							lure.scratchpad.insert("hidden".to_string(), 0.0);
-						} else if [30.0, 40.0, 60.0, 70.0, 90.0, 100.0].contains(&status) {
-							fisherman.increment_frame(Vec2::X);
-						} else if status > 300.0 {
-							status = 1.0;	//Restart animation at start
-							fisherman.set_frame(Vec2::ZERO);
-							lure.scratchpad.insert("hidden".to_string(), 1.0);
-						} else if status == 0.0 {
-							//Reset lure
-							lure.scratchpad.insert("hidden".to_string(), 1.0);
-							fisherman.set_frame(Vec2::ZERO);
-
-
-							/* 
-							if fisherman.vel.x >= 0.0 {
-								fisherman.vel.x = 3.0;
-								fisherman.params.flip_x = true;
-							} else {
-								fisherman.vel.x = -3.0;
-								fisherman.params.flip_x = false;
-							}
-							if let Some(x_index) = fisherman.scratchpad.get("x-index") {
-								if (-500.0 + x_index) > fisherman.pos.x {
-									fisherman.pos.x = -500.0 + x_index;
-									fisherman.vel.x = 3.0;
-								}
-								if fisherman.pos.x > (1000.0 + x_index) {
-									fisherman.pos.x = 1000.0 + x_index;
-									fisherman.vel.x = -3.0;
-								}
-							}
-							*/
-						}
-					}
-				}
-				fisherman.scratchpad.insert("status".to_string(), status);
-			}
-		});
-		fisherman.do_collide = Some(|axis, a, b, intersection| {
-			if axis.y == 1.0 && b.vel.y > 0.1 {
-				a.vel.y -= 15.0;
-			}
-			
-			let collision_result = Article::default_collide(axis, a, b, intersection);
-			if axis.y == 1.0 && b.vel.y > 0.1 {
-				a.vel.y += 15.0;
-			}
-			if axis.x == 1.0 {
-				a.vel.x *= -1.0;
+	fn player(x: f32, y: f32) -> String {
+		format!(r#"
+		(
+			name: "Player",
+			src: (0.0, 0.0, 315.0, 480.0),
+			dest: ({x:.1}, {y:.1}, 90.0, 140.0),
+			bounds: [(25.0, 20.0, 40.0, 88.0)],
+			mass: 5.0,
+			elasticity: 0.5,
+			cog: (44.5, 66.0),
+			behaviour: "player",
+			components: (
+				health: (current: 5.0, max: 5.0),
+				hookable: (),
+				poses: (current: "standing", sets: {{
+					"standing": (bounds: [(25.0, 20.0, 40.0, 88.0)]),
+					"sliding": (bounds: [(7.0, 50.0, 88.0, 40.0)], rotation: 1.5707964),
+				}}),
+			),
+		),"#)
+	}
+
+	fn platform(i: i32, x: f32, y: f32) -> String {
+		format!(r#"
+		(
+			name: "Platform-{i}",
+			src: (0.0, 100.0, 400.0, 120.0),
+			dest: ({x:.1}, {y:.1}, 400.0, 120.0),
+			bounds: [(0.0, 60.0, 400.0, 60.0)],
+			one_way: [0],
+			mass: 100000000.0,
+			elasticity: 0.0,
+			behaviour: "platform",
+		),"#)
+	}
+
+	fn build_world(articles: &[String]) -> World {
+		let source = format!("#![enable(implicit_some)]\nLevel(name: \"Test\", articles: [{}])", articles.concat());
+		let level = Level::parse(&source).unwrap();
+		World::new(level.name.clone(), level.build_articles(&HashMap::new(), &HashMap::new(), &behaviour::registry()))
+	}
+
+	fn run(world: &mut World, input: &InputState, frames: usize) {
+		for _ in 0..frames {
+			world.step(input);
+		}
+	}
+
+	fn player_feet(world: &World) -> f32 {
+		let player = &world.articles["Player"];
+		let bound = player.bounds.as_ref().unwrap()[0];
+		player.pos.y + bound.y + bound.h
+	}
+
+	#[test]
+	fn one_second_of_steps_falls_by_gravity() {
+		let mut world = build_world(&[player(0.0, -2000.0)]);
+		run(&mut world, &InputState::default(), TICK_RATE as usize);
+
+		//Half of g*t^2 for a second of 1440px/s^2 gravity, less a little for drag and the discrete steps
+		let fallen = world.articles["Player"].pos.y + 2000.0;
+		assert!((650.0..=740.0).contains(&fallen), "fell {}", fallen);
+		assert!((world.articles["Player"].vel.y - 1440.0).abs() < 250.0);
+	}
+
+	#[test]
+	fn player_falls_and_rests_on_ground() {
+		let mut world = build_world(&[GROUND.to_string(), player(0.0, 0.0)]);
+		run(&mut world, &InputState::default(), 120);
+
+		let player = &world.articles["Player"];
+		assert_eq!(player.attached.as_deref(), Some("Ground"));
+		assert!(player.vel.length() < 0.05);
+		assert!((player_feet(&world) - 556.0).abs() <= 2.0, "feet at {}", player_feet(&world));
+	}
+
+	#[test]
+	fn platforms_carry_riders_whatever_they_are_named() {
+		let lift = platform(0, 0.0, 200.0).replace("Platform-0", "Lift");
+		let mut world = build_world(&[GROUND.to_string(), lift, player(150.0, -200.0)]);
+		run(&mut world, &InputState::default(), 60);
+		assert_eq!(world.articles["Player"].attached.as_deref(), Some("Lift"));
+		assert!(world.articles["Lift"].attached_to.contains(&"Player".to_string()));
+	}
+
+	#[test]
+	fn landing_on_a_platform_moved_by_its_tick() {
+		//Past the bottom of its track, so its tick puts it back at 400 before anything moves
+		let lift = platform(0, 0.0, 800.0).replace("Platform-0", "Zlift");
+		let mut world = build_world(&[lift, player(150.0, 350.0)]);
+		world.articles.get_mut("Player").unwrap().vel.y = 600.0;
+		run(&mut world, &InputState::default(), 10);
+		assert_eq!(world.articles["Player"].attached.as_deref(), Some("Zlift"));
+	}
+
+	#[test]
+	fn player_lands_on_platform_and_stays_attached() {
+		let mut world = build_world(&[GROUND.to_string(), platform(2, 0.0, 200.0), player(150.0, -200.0)]);
+		run(&mut world, &InputState::default(), 60);
+		assert_eq!(world.articles["Player"].attached.as_deref(), Some("Platform-2"));
+
+		//The platform keeps rising, the player should ride it rather than sink through or float off
+		for _ in 0..100 {
+			world.step(&InputState::default());
+			let platform = &world.articles["Platform-2"];
+			let platform_top = platform.pos.y + 60.0;
+			assert_eq!(world.articles["Player"].attached.as_deref(), Some("Platform-2"));
+			assert!((player_feet(&world) - platform_top).abs() <= 8.0, "feet {} platform {}", player_feet(&world), platform_top);
+		}
+	}
+
+	#[test]
+	fn holding_right_walks_the_player_right() {
+		let mut world = build_world(&[GROUND.to_string(), player(0.0, 380.0)]);
+		run(&mut world, &InputState::default(), 30);
+		let start = world.articles["Player"].pos.x;
+
+		run(&mut world, &InputState::with_actions(&[Action::MoveRight]), 60);
+		let player = &world.articles["Player"];
+		assert!(player.pos.x > start + 100.0);
+		assert!(player.params.flip_x);
+	}
+
+	#[test]
+	fn jumping_leaves_the_ground_and_lands_again() {
+		let mut world = build_world(&[GROUND.to_string(), player(0.0, 380.0)]);
+		run(&mut world, &InputState::default(), 30);
+		let rest = player_feet(&world);
+
+		world.step(&InputState::with_actions(&[Action::Jump]));
+		run(&mut world, &InputState::default(), 10);
+		assert!(player_feet(&world) < rest - 100.0);
+
+		run(&mut world, &InputState::default(), 120);
+		assert!((player_feet(&world) - rest).abs() <= 2.0);
+		assert_eq!(world.articles["Player"].attached.as_deref(), Some("Ground"));
+	}
+
+	#[test]
+	fn sliding_player_only_stands_up_where_there_is_room() {
+		let ceiling = r#"
+		(
+			name: "Ceiling",
+			src: (0.0, 0.0, 600.0, 100.0),
+			dest: (0.0, 400.0, 600.0, 100.0),
+			bounds: [(0.0, 0.0, 600.0, 100.0)],
+			mass: inf,
+		),"#;
+		let mut world = build_world(&[GROUND.to_string(), ceiling.to_string(), player(-300.0, 380.0)]);
+		run(&mut world, &InputState::default(), 30);
+		let rest = player_feet(&world);
+
+		run(&mut world, &InputState::with_actions(&[Action::Slide]), 5);
+		let player = &world.articles["Player"];
+		assert!(player.is_pose("sliding"));
+		assert_eq!(player.bounding_box().unwrap().h, 40.0);
+		assert!((player_feet(&world) - rest).abs() <= 2.0);
+
+		//Slid under the ceiling, letting go of slide leaves it on its belly
+		world.articles.get_mut("Player").unwrap().pos.x += 500.0;
+		run(&mut world, &InputState::default(), 5);
+		assert!(world.articles["Player"].is_pose("sliding"));
+
+		world.articles.get_mut("Player").unwrap().pos.x += 500.0;
+		run(&mut world, &InputState::default(), 5);
+		let player = &world.articles["Player"];
+		assert!(player.is_pose("standing"));
+		assert_eq!(player.bounding_box().unwrap().h, 88.0);
+		assert!((player_feet(&world) - rest).abs() <= 2.0);
+	}
+
+	fn ramp(direction: f32) -> String {
+		format!(r#"
+		(
+			name: "Ramp",
+			src: (0.0, 0.0, 400.0, 200.0),
+			dest: (0.0, 356.0, 400.0, 200.0),
+			polygons: [[(0.0, 200.0), (400.0, 0.0), (400.0, 200.0)]],
+			mass: inf,
+			direction: ({direction:.1}, 0.0),
+		),"#)
+	}
+
+	#[test]
+	fn player_stands_still_on_a_slope() {
+		//Rises to the right, so the player's right foot rests on it
+		let mut world = build_world(&[GROUND.to_string(), ramp(-1.0), player(150.0, 290.0)]);
+		run(&mut world, &InputState::default(), 90);
+		assert_eq!(world.articles["Player"].attached.as_deref(), Some("Ramp"));
+
+		let rest = world.articles["Player"].pos;
+		for _ in 0..60 {
+			world.step(&InputState::default());
+			assert!((world.articles["Player"].pos - rest).length() < 0.5, "moved to {}", world.articles["Player"].pos);
+		}
+
+		//Uphill, stopping short of the top
+		run(&mut world, &InputState::with_actions(&[Action::MoveRight]), 15);
+		let player = &world.articles["Player"];
+		assert!(player.pos.x > rest.x + 50.0 && player.pos.y < rest.y - 25.0, "walked to {}", player.pos);
+		assert_eq!(player.attached.as_deref(), Some("Ramp"));
+	}
+
+	#[test]
+	fn player_slips_down_an_icy_slope() {
+		let icy = ramp(-1.0).replace("mass: inf,", "polygon_materials: [Ice],\n\t\t\tmass: inf,");
+		let mut world = build_world(&[GROUND.to_string(), icy, player(150.0, 290.0)]);
+		run(&mut world, &InputState::default(), 30);
+		let start = world.articles["Player"].pos;
+		assert_eq!(world.articles["Player"].footing, Some(Material::Ice));
+
+		run(&mut world, &InputState::default(), 30);
+		let player = &world.articles["Player"];
+		assert!(player.pos.x < start.x - 20.0 && player.pos.y > start.y, "slid to {} from {}", player.pos, start);
+	}
+
+	#[test]
+	fn sliding_player_speeds_up_down_a_slope_and_keeps_going() {
+		//Mirrored to fall to the right
+		let mut world = build_world(&[GROUND.to_string(), ramp(1.0), player(50.0, 246.0)]);
+		let slide = InputState::with_actions(&[Action::Slide]);
+		run(&mut world, &slide, 30);
+		assert_eq!(world.articles["Player"].attached.as_deref(), Some("Ramp"));
+		let speed = world.articles["Player"].vel.x;
+		assert!(speed > 50.0, "speed {}", speed);
+
+		run(&mut world, &slide, 10);
+		assert!(world.articles["Player"].vel.x > speed);
+
+		//Off the bottom of the ramp and onto flat ground without losing much speed
+		run(&mut world, &slide, 60);
+		let player = &world.articles["Player"];
+		assert!(player.pos.x > 400.0);
+		assert_eq!(player.attached.as_deref(), Some("Ground"));
+		assert!(player.vel.x > speed);
+	}
+
+	#[test]
+	fn player_jumps_up_through_a_one_way_ledge_and_drops_back_down() {
+		let ledge = r#"
+		(
+			name: "Ledge",
+			src: (0.0, 0.0, 400.0, 40.0),
+			dest: (-200.0, 300.0, 400.0, 40.0),
+			bounds: [(0.0, 0.0, 400.0, 40.0)],
+			one_way: [0],
+			mass: inf,
+		),"#;
+		let mut world = build_world(&[GROUND.to_string(), ledge.to_string(), player(0.0, 380.0)]);
+		run(&mut world, &InputState::default(), 30);
+		let ground = player_feet(&world);
+
+		run(&mut world, &InputState::with_actions(&[Action::Jump]), 20);
+		run(&mut world, &InputState::default(), 120);
+		assert_eq!(world.articles["Player"].attached.as_deref(), Some("Ledge"));
+		assert!((player_feet(&world) - 300.0).abs() <= 2.0, "feet at {}", player_feet(&world));
+
+		//Down on its own doesn't drop, and Down with Jump doesn't jump
+		run(&mut world, &InputState::with_actions(&[Action::Down]), 10);
+		assert_eq!(world.articles["Player"].attached.as_deref(), Some("Ledge"));
+		world.step(&InputState::with_actions(&[Action::Down, Action::Jump]));
+		run(&mut world, &InputState::with_actions(&[Action::Down, Action::Jump]), 5);
+		assert!(player_feet(&world) > 300.0);
+		run(&mut world, &InputState::default(), 120);
+		assert_eq!(world.articles["Player"].attached.as_deref(), Some("Ground"));
+		assert!((player_feet(&world) - ground).abs() <= 2.0);
+		assert_eq!(world.articles["Player"].dropping_through, None);
+	}
+
+	#[test]
+	fn dropping_through_with_the_default_keys_does_not_slide() {
+		let ledge = r#"
+		(
+			name: "Ledge",
+			src: (0.0, 0.0, 400.0, 40.0),
+			dest: (-200.0, 300.0, 400.0, 40.0),
+			bounds: [(0.0, 0.0, 400.0, 40.0)],
+			one_way: [0],
+			mass: inf,
+		),"#;
+		let mut world = build_world(&[GROUND.to_string(), ledge.to_string(), player(0.0, 150.0)]);
+		run(&mut world, &InputState::default(), 60);
+		assert_eq!(world.articles["Player"].attached.as_deref(), Some("Ledge"));
+
+		//S is bound to both Down and Slide
+		let config = InputConfig::default();
+		run(&mut world, &InputState::with_keys(&config, &[KeyCode::S, KeyCode::Space]), 5);
+		let player = &world.articles["Player"];
+		assert!(player.is_pose("standing"));
+		assert!(player_feet(&world) > 300.0);
+		assert_eq!(player.dropping_through.as_deref(), Some("Ledge"));
+	}
+
+	/**
+	 * Walk right for a second on ground of the given material, then let go and see how much speed is left after a moment
+	 */
+	fn coast_on(material: &str) -> (f32, Option<Material>) {
+		let ground = GROUND.replace("mass: inf,", &format!("materials: [{material}],\n\t\t\tmass: inf,"));
+		let mut world = build_world(&[ground, player(0.0, 380.0)]);
+		run(&mut world, &InputState::default(), 30);
+		run(&mut world, &InputState::with_actions(&[Action::MoveRight]), TICK_RATE as usize);
+		let speed = world.articles["Player"].vel.x;
+		assert!(speed > 100.0, "only got up to {speed} on {material}");
+		run(&mut world, &InputState::default(), TICK_RATE as usize / 2);
+		let player = &world.articles["Player"];
+		(player.vel.x / speed, player.footing)
+	}
+
+	#[test]
+	fn player_slides_on_ice_and_grips_grass() {
+		let (kept_on_grass, grass_footing) = coast_on("Grass");
+		let (kept_on_ice, ice_footing) = coast_on("Ice");
+		assert_eq!(grass_footing, Some(Material::Grass));
+		assert_eq!(ice_footing, Some(Material::Ice));
+		assert!(kept_on_grass < 0.1, "kept {kept_on_grass} of its speed on grass");
+		assert!(kept_on_ice > 0.5, "kept {kept_on_ice} of its speed on ice");
+	}
+
+	#[test]
+	fn player_floats_in_water_and_heavy_things_sink() {
+		let pool = r#"
+		(
+			name: "Water-0",
+			src: (0.0, 0.0, 600.0, 400.0),
+			dest: (-300.0, 156.0, 600.0, 400.0),
+			bounds: [(0.0, 0.0, 600.0, 400.0)],
+			mass: inf,
+			trigger: true,
+			components: (water: ()),
+		),"#;
+		let anchor = r#"
+		(
+			name: "Anchor",
+			src: (0.0, 0.0, 64.0, 64.0),
+			dest: (150.0, 0.0, 64.0, 64.0),
+			bounds: [(0.0, 0.0, 64.0, 64.0)],
+			mass: 50.0,
+			elasticity: 0.0,
+		),"#;
+		let mut world = build_world(&[GROUND.to_string(), pool.to_string(), anchor.to_string(), player(-50.0, 0.0)]);
+		run(&mut world, &InputState::default(), 3 * TICK_RATE as usize);
+		let splashes = |world: &mut World| world.take_events().iter().filter(|event| matches!(event, Event::Splash(_))).count();
+		assert_eq!(splashes(&mut world), 2);
+
+		let player = &world.articles["Player"];
+		assert_eq!(player.in_water.as_deref(), Some("Water-0"));
+		assert!(player.is_pose("sliding"));
+		assert!(player.submerged > 0.5 && player.submerged < 1.0, "{} under water", player.submerged);
+		assert_eq!(player.attached, None);
+		assert_eq!(world.articles["Anchor"].attached.as_deref(), Some("Ground"));
+
+		//A stroke at the surface leaps out of the water
+		run(&mut world, &InputState::with_actions(&[Action::Jump]), 10);
+		assert_eq!(world.articles["Player"].in_water, None);
+		assert_eq!(splashes(&mut world), 1);
+	}
+
+	#[test]
+	fn articles_draw_by_layer_then_z_and_keep_their_order() {
+		let decoration = |name: &str, extra: &str| format!(r#"
+		(
+			name: "{name}",
+			src: (0.0, 0.0, 10.0, 10.0),
+			dest: (0.0, 0.0, 10.0, 10.0),
+			mass: inf,
+			{extra}
+		),"#);
+		let ground = GROUND.replace("mass: inf,", "mass: inf,\n\t\t\tlayer: Back,");
+		let world = build_world(&[
+			decoration("Bush", "layer: Front,"),
+			decoration("Banner", "z: 1,"),
+			decoration("Anvil", ""),
+			ground,
+			player(0.0, 380.0),
+		]);
+		let order = world.draw_order();
+		assert_eq!(order, ["Ground", "Anvil", "Player", "Banner", "Bush"]);
+		assert_eq!(world.draw_order(), order);
+	}
+
+	#[test]
+	fn post_physics_hooks_see_where_everything_ended_up() {
+		fn follow_player(marker: &mut Article, articles: &mut HashMap<String, Article>) {
+			if let Some(player) = articles.get("Player") {
+				marker.pos = player.pos - vec2(0.0, 50.0);
 			}
-			collision_result
-		});
-		articles.insert(fisherman.name.clone(), fisherman);
+		}
+		let marker = r#"
+		(
+			name: "Marker",
+			src: (0.0, 0.0, 10.0, 10.0),
+			dest: (0.0, 0.0, 10.0, 10.0),
+			mass: inf,
+		),"#;
+		let source = format!("#![enable(implicit_some)]\nLevel(name: \"Test\", articles: [{}{}{}])", GROUND, marker, player(0.0, 380.0));
+		let mut articles = Level::parse(&source).unwrap().build_articles(&HashMap::new(), &HashMap::new(), &behaviour::registry());
+		articles.get_mut("Marker").unwrap().post_physics = Some(follow_player);
+		let mut world = World::new("Test".to_string(), articles);
+
+		//The marker is updated before the player in every phase, so it would lag a step behind if it followed during the tick
+		for _ in 0..30 {
+			world.step(&InputState::with_actions(&[Action::MoveRight]));
+			assert_eq!(world.articles["Marker"].pos, world.articles["Player"].pos - vec2(0.0, 50.0));
+		}
+	}
+
+	#[test]
+	fn level_files_parse_with_components() {
+		for path in ["res/levels/level_1.ron", "res/levels/level_2.ron"] {
+			let level = Level::parse(&std::fs::read_to_string(path).unwrap()).unwrap();
+			let articles = level.build_articles(&HashMap::new(), &HashMap::new(), &behaviour::registry());
+			let health = articles["Player"].components.health.as_ref().unwrap();
+			assert_eq!((health.current, health.max), (5.0, 5.0));
+			assert!(articles["Player"].components.hookable.is_some());
+		}
+	}
+
+	#[test]
+	fn walking_into_an_exit_finishes_the_level_whatever_it_is_called() {
+		let door = r#"
+		(
+			name: "Door",
+			src: (0.0, 0.0, 120.0, 176.0),
+			dest: (200.0, 380.0, 120.0, 176.0),
+			bounds: [(0.0, 0.0, 120.0, 176.0)],
+			mass: inf,
+			behaviour: "exit",
+			trigger: true,
+			components: (exit: ()),
+		),"#;
+		let mut world = build_world(&[GROUND.to_string(), door.to_string(), player(0.0, 380.0)]);
+		run(&mut world, &InputState::with_actions(&[Action::MoveRight]), 60);
+		assert!(world.take_events().contains(&Event::LevelExit));
+	}
+
+	#[test]
+	fn level_player_and_spiders_play_their_clips() {
+		let level = Level::parse(&std::fs::read_to_string("res/levels/level_1.ron").unwrap()).unwrap();
+		let mut world = World::from_level(&level, &HashMap::new(), &level.read_sheets());
+		let clip = |world: &World, name: &str| world.articles[name].components.animation.as_ref().unwrap().playing().to_string();
+		run(&mut world, &InputState::default(), 60);
+		assert_eq!(clip(&world, "Player"), "idle");
+		assert_eq!(clip(&world, "Enemy-1"), "walk");
+
+		run(&mut world, &InputState::with_actions(&[Action::MoveRight]), 30);
+		assert_eq!(clip(&world, "Player"), "walk");
+		run(&mut world, &InputState::with_actions(&[Action::MoveRight, Action::Slide]), 5);
+		assert_eq!(clip(&world, "Player"), "slide");
+	}
 
+	#[test]
+	fn hazards_hurt_and_knock_back_anything_with_health() {
+		let spikes = r#"
+		(
+			name: "Spikes-0",
+			src: (0.0, 0.0, 200.0, 40.0),
+			dest: (-300.0, 516.0, 400.0, 40.0),
+			bounds: [(0.0, 10.0, 400.0, 30.0)],
+			mass: inf,
+			behaviour: "hazard",
+			components: (hazard: (damage: 1.0, knockback: (300.0, -900.0))),
+		),"#;
+		let fire = r#"
+		(
+			name: "Fire-0",
+			src: (0.0, 0.0, 16.0, 16.0),
+			dest: (400.0, 508.0, 48.0, 48.0),
+			bounds: [(8.0, 8.0, 32.0, 40.0)],
+			mass: inf,
+			trigger: true,
+			behaviour: "hazard",
+			components: (hazard: (damage: 2.0)),
+		),"#;
+		let snowman = r#"
+		(
+			name: "Snowman",
+			src: (0.0, 0.0, 64.0, 64.0),
+			dest: (392.0, 300.0, 64.0, 64.0),
+			bounds: [(0.0, 0.0, 64.0, 64.0)],
+			mass: 10.0,
+			elasticity: 0.0,
+			components: (health: (current: 3.0, max: 3.0)),
+		),"#;
+		//No collide of its own, so only the spikes' side of the contact can hurt it
+		let snowball = snowman.replace("Snowman", "Snowball").replace("(392.0, 300.0", "(-250.0, 300.0");
+		let mut world = build_world(&[GROUND.to_string(), spikes.to_string(), fire.to_string(), snowman.to_string(), snowball, player(-30.0, 300.0)]);
+		let mut thrown = false;
+		for _ in 0..60 {
+			world.step(&InputState::default());
+			let player = &world.articles["Player"];
+			thrown |= player.vel.y < 0.0 && player.vel.x > 0.0;
+		}
+		assert!(thrown, "player was never knocked back off the spikes");
+		let health = |world: &World, name: &str| world.articles[name].components.health.as_ref().unwrap().current;
+		assert_eq!(health(&world, "Player"), 4.0);
+		assert_eq!(health(&world, "Snowman"), 1.0);
+		assert_eq!(health(&world, "Snowball"), 2.0);
+		assert_eq!(world.articles["Snowman"].attached.as_deref(), Some("Ground"));	//Fell through the fire
+	}
 
+	#[test]
+	fn spider_turns_around_at_the_end_of_its_patrol() {
+		let spider = r#"
+		(
+			name: "Enemy-0",
+			src: (0.0, 0.0, 256.0, 128.0),
+			dest: (0.0, 372.0, -300.0, 128.0),
+			bounds: [(47.0, 33.0, 129.0, 62.0)],
+			mass: 1000000.0,
+			elasticity: 1.0,
+			vel: (1200.0, 0.0),
+			behaviour: "spider",
+			components: (patrol: (origin_x: 0.0, left: 100.0, right: 200.0)),
+		),"#;
+		let mut world = build_world(&[GROUND.to_string(), spider.to_string()]);
+		let (mut min_x, mut max_x) = (f32::MAX, f32::MIN);
+		for _ in 0..240 {
+			world.step(&InputState::default());
+			let x = world.articles["Enemy-0"].pos.x;
+			min_x = min_x.min(x);
+			max_x = max_x.max(x);
+		}
+		assert!((-110.0..-50.0).contains(&min_x), "min {}", min_x);
+		assert!((150.0..=210.0).contains(&max_x), "max {}", max_x);
+	}
 
+	#[test]
+	fn fisherman_throws_the_lure_when_the_cast_clip_finishes() {
+		let level = Level::parse(&std::fs::read_to_string("res/levels/level_1.ron").unwrap()).unwrap();
+		let sheet_path = "res/textures/fisherman_spritesheet.ron";
+		let sheet = SpriteSheet::parse(&std::fs::read_to_string(sheet_path).unwrap()).unwrap();
+		let sheets = HashMap::from([(sheet_path.to_string(), Rc::new(sheet))]);
+		let mut world = World::new(level.name.clone(), level.build_articles(&HashMap::new(), &sheets, &behaviour::registry()));
+		let lure_hidden = |world: &World| world.articles["lure-fisherman-0"].components.lure.as_ref().unwrap().hidden;
 
+		//The cast clip lasts 1.65 seconds from when the player is spotted
+		run(&mut world, &InputState::default(), 97);
+		assert!(lure_hidden(&world));
+		run(&mut world, &InputState::default(), 3);
+		assert!(!lure_hidden(&world));
+		let fisherman = &world.articles["fisherman-0"];
+		assert_eq!(fisherman.params.source.unwrap().x, 5.0 * 384.0);
 
-		articles
+		//Facing right the throw frame's pole tip is mirrored across the 384px frame
+		let pole = world.articles["lure-fisherman-0"].components.lure.as_ref().unwrap().pole.unwrap();
+		assert!(pole.distance(fisherman.pos + vec2(384.0 - 103.0, 197.0)) < 0.01);
 	}
 
-	
+	#[test]
+	fn player_is_defeated_when_health_runs_out() {
+		let mut world = build_world(&[GROUND.to_string(), player(0.0, 380.0)]);
+		for _ in 0..4 {
+			world.articles.get_mut("Player").unwrap().update_health(-1.0);
+			run(&mut world, &InputState::default(), 2 * TICK_RATE as usize);
+		}
+		assert!(!world.player_defeated());
+
+		world.articles.get_mut("Player").unwrap().update_health(-1.0);
+		assert!(world.player_defeated());
+	}
+
+	const CHECKPOINT: &str = r#"
+		(
+			name: "Checkpoint-0",
+			src: (0.0, 0.0, 32.0, 64.0),
+			dest: (600.0, 428.0, 64.0, 128.0),
+			bounds: [(0.0, 0.0, 64.0, 128.0)],
+			mass: inf,
+			behaviour: "checkpoint",
+			components: (checkpoint: (spawn: (0.0, -40.0))),
+		),"#;
+
+	const CRATE: &str = r#"
+		(
+			name: "Crate-0",
+			src: (0.0, 0.0, 64.0, 64.0),
+			dest: (-800.0, 492.0, 64.0, 64.0),
+			bounds: [(0.0, 0.0, 64.0, 64.0)],
+			mass: 10.0,
+			reset_on_respawn: true,
+		),"#;
+
+	#[test]
+	fn respawn_returns_to_the_last_checkpoint_with_full_health() {
+		let mut world = build_world(&[GROUND.to_string(), CHECKPOINT.to_string(), CRATE.to_string(), player(0.0, 380.0)]);
+		run(&mut world, &InputState::with_actions(&[Action::MoveRight]), 90);
+		assert!(world.articles["Player"].pos.x > 700.0);
+
+		world.articles.get_mut("Player").unwrap().update_health(-3.0);
+		world.articles.get_mut("Crate-0").unwrap().do_destroy = true;
+		world.step(&InputState::default());
+		assert!(!world.articles.contains_key("Crate-0"));
+
+		world.respawn(true);
+		let player = &world.articles["Player"];
+		assert_eq!(player.pos, vec2(600.0, 388.0));
+		assert_eq!(player.vel, Vec2::ZERO);
+		assert_eq!(player.components.health.as_ref().unwrap().current, 5.0);
+		assert_eq!(world.articles["Crate-0"].pos, vec2(-800.0, 492.0));
+
+		//The restored crate is simulated again
+		run(&mut world, &InputState::default(), 30);
+		assert_eq!(world.articles["Player"].attached.as_deref(), Some("Ground"));
+		assert_eq!(world.articles["Crate-0"].attached.as_deref(), Some("Ground"));
+	}
 
-}
\ No newline at end of file
+	#[test]
+	fn checkpoints_are_reached_whatever_they_are_named() {
+		let flag = CHECKPOINT.replace("Checkpoint-0", "Flag");
+		let mut world = build_world(&[GROUND.to_string(), flag, player(0.0, 380.0)]);
+		run(&mut world, &InputState::with_actions(&[Action::MoveRight]), 90);
+		assert!(world.take_events().contains(&Event::CheckpointReached(vec2(600.0, 388.0))));
+	}
+
+	#[test]
+	fn asking_to_respawn_keeps_the_damage_taken() {
+		let mut world = build_world(&[GROUND.to_string(), player(0.0, 380.0)]);
+		run(&mut world, &InputState::with_actions(&[Action::MoveRight]), 30);
+		world.articles.get_mut("Player").unwrap().update_health(-2.0);
+		world.step(&InputState::with_actions(&[Action::Respawn]));
+
+		let player = &world.articles["Player"];
+		assert_eq!(player.pos, vec2(0.0, 380.0));
+		assert_eq!(player.components.health.as_ref().unwrap().current, 3.0);
+	}
+
+	#[test]
+	fn falling_out_of_bounds_counts_as_a_death() {
+		let mut world = build_world(&[player(0.0, 380.0).replace("behaviour", "out_of_bounds: Kill,\n\t\t\tbehaviour")]);
+		world.bounds = Some(Rect::new(-1000.0, -1000.0, 2000.0, 3000.0));
+		run(&mut world, &InputState::default(), 3 * TICK_RATE as usize);
+		assert!(world.player_defeated());
+		assert_eq!(world.take_events().iter().filter(|event| **event == Event::PlayerDied).count(), 1);
+
+		world.respawn(true);
+		assert_eq!(world.articles["Player"].pos, vec2(0.0, 380.0));
+		assert!(!world.player_defeated());
+	}
+
+	#[test]
+	fn falling_out_of_bounds_costs_the_damage_amount() {
+		let mut world = build_world(&[GROUND.to_string(), player(0.0, 380.0).replace("behaviour", "out_of_bounds: Damage(2.0),\n\t\t\tbehaviour")]);
+		world.bounds = Some(Rect::new(-1000.0, -1000.0, 2000.0, 3000.0));
+		run(&mut world, &InputState::default(), 30);
+		world.articles.get_mut("Player").unwrap().pos = vec2(0.0, 2500.0);
+		world.step(&InputState::default());
+
+		let player = &world.articles["Player"];
+		assert_eq!(player.pos, vec2(0.0, 380.0));
+		assert_eq!(player.components.health.as_ref().unwrap().current, 3.0);
+		assert!(!world.save_game("").player_dead);
+	}
+
+	#[test]
+	fn lethal_out_of_bounds_damage_leaves_the_player_dead() {
+		let mut world = build_world(&[GROUND.to_string(), player(0.0, 380.0).replace("behaviour", "out_of_bounds: Damage(5.0),\n\t\t\tbehaviour")]);
+		world.bounds = Some(Rect::new(-1000.0, -1000.0, 2000.0, 3000.0));
+		world.articles.get_mut("Player").unwrap().pos = vec2(0.0, 2500.0);
+		world.step(&InputState::default());
+
+		assert!(world.save_game("").player_dead);
+		assert!(world.player_defeated());
+		assert_eq!(world.take_events().iter().filter(|event| **event == Event::PlayerDied).count(), 1);
+	}
+
+	#[test]
+	fn articles_leaving_bounds_follow_their_policy() {
+		let crate_with = |name: &str, x: f32, policy: &str| format!(r#"
+		(
+			name: "{name}",
+			src: (0.0, 0.0, 64.0, 64.0),
+			dest: ({x:.1}, 0.0, 64.0, 64.0),
+			bounds: [(0.0, 0.0, 64.0, 64.0)],
+			mass: 10.0,
+			out_of_bounds: {policy},
+		),"#);
+		let mut world = build_world(&[
+			crate_with("Lost", 0.0, "Destroy"),
+			crate_with("Returning", 200.0, "Respawn"),
+			crate_with("Parked", 400.0, "Ignore"),
+		]);
+		world.bounds = Some(Rect::new(-1000.0, -1000.0, 2000.0, 2000.0));
+		run(&mut world, &InputState::default(), 2 * TICK_RATE as usize);
+
+		assert!(!world.articles.contains_key("Lost"));
+		assert!(world.articles["Returning"].pos.y < 1000.0);
+		assert!(world.articles["Parked"].pos.y > 1000.0);
+	}
+
+	#[test]
+	fn damage_is_ignored_until_invulnerability_wears_off() {
+		let mut world = build_world(&[GROUND.to_string(), player(0.0, 380.0)]);
+		let health = |world: &World| world.articles["Player"].components.health.as_ref().unwrap().current;
+
+		world.articles.get_mut("Player").unwrap().update_health(-1.0);
+		world.articles.get_mut("Player").unwrap().update_health(-1.0);
+		assert_eq!(health(&world), 4.0);
+
+		//Two seconds by default, one step short of that the player still can't be hurt
+		run(&mut world, &InputState::default(), 2 * TICK_RATE as usize - 1);
+		world.articles.get_mut("Player").unwrap().update_health(-1.0);
+		assert_eq!(health(&world), 4.0);
+
+		world.step(&InputState::default());
+		world.articles.get_mut("Player").unwrap().update_health(-1.0);
+		assert_eq!(health(&world), 3.0);
+	}
+
+	/**
+	 * Static 64px tiles laid out as a floor with the player and a row of crates dropped on top
+	 */
+	fn tiled_world(tile_count: usize) -> World {
+		let mut articles = HashMap::new();
+		let columns = 200;
+		for i in 0..tile_count {
+			let (x, y) = ((i % columns) as f32 * 64.0, 500.0 + (i / columns) as f32 * 64.0);
+			let mut tile = Article::new(Rect::new(0.0, 0.0, 64.0, 64.0), Rect::new(x, y, 64.0, 64.0), Some(vec![Rect::new(0.0, 0.0, 64.0, 64.0)]));
+			tile.name = format!("Tile-{}", i);
+			tile.mass = f32::INFINITY;
+			articles.insert(tile.name.clone(), tile);
+		}
+		for i in 0..20 {
+			let mut block = Article::new(Rect::new(0.0, 0.0, 64.0, 64.0), Rect::new(300.0 + i as f32 * 400.0, 300.0, 64.0, 64.0), Some(vec![Rect::new(0.0, 0.0, 64.0, 64.0)]));
+			block.name = format!("Block-{}", i);
+			articles.insert(block.name.clone(), block);
+		}
+		let mut world = build_world(&[player(100.0, 300.0)]);
+		articles.extend(world.articles.drain());
+		World::new("Bench".to_string(), articles)
+	}
+
+	/**
+	 * A timing check rather than a correctness test, so it's ignored by default and only worth running optimised:
+	 * `cargo test --release bench_step_with_thousands_of_static_tiles -- --ignored --nocapture` prints the per step cost as the tile count grows.
+	 * It lives here instead of under benches/ because the game is a binary crate, which a bench target can't link against
+	 */
+	#[test]
+	#[ignore]
+	fn bench_step_with_thousands_of_static_tiles() {
+		let steps = 300;
+		let mut timings = Vec::new();
+		for tile_count in [1_000, 4_000, 16_000] {
+			let mut world = tiled_world(tile_count);
+			let start = std::time::Instant::now();
+			run(&mut world, &InputState::with_actions(&[Action::MoveRight]), steps);
+			let per_step = start.elapsed().as_secs_f64() * 1000.0 / steps as f64;
+			println!("{:>6} tiles: {:.4}ms per step", tile_count, per_step);
+			timings.push(per_step);
+		}
+		//Only the few tiles near moving articles are tested, so 16x the tiles shouldn't cost anywhere near 16x
+		assert!(timings[2] < timings[0] * 4.0);
+	}
+}